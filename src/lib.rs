@@ -1,4 +1,12 @@
 pub mod toqst {
+    use std::{
+        env,
+        str::FromStr,
+        time::{Duration, Instant},
+    };
+
+    use color_eyre::eyre::bail;
+
     use ratatui::{
         style::{Color, Modifier, Style},
         text::Span,
@@ -11,8 +19,22 @@ pub mod toqst {
     // User types the correct letter (not a space)
     pub const CORRECT_COLOR: Color = Color::Green;
     // User types a letter when it should have been a space
-    pub const MISTYPE_EXTRA_COLOR: Color = Color::Red;
+    // Darker than a mistype and crossed out, so it can be told apart even without color
+    pub const MISTYPE_EXTRA_COLOR: Color = Color::Rgb(150, 0, 0);
+    pub const MISTYPE_EXTRA_MODIFIER: Modifier = Modifier::CROSSED_OUT;
+    // Correct letter typed slowly when coloring by typing speed, unless the theme's correct
+    // color is given as RGB or from the 256 color palette
+    pub const SLOW_CORRECT_RGB: (u8, u8, u8) = (0, 160, 0);
+    // Correct letter typed quickly when coloring by typing speed
+    pub const FAST_CORRECT_RGB: (u8, u8, u8) = (0, 255, 255);
+    // Keystroke intervals at or above this are colored fully slow (~30 wpm)
+    pub const SLOW_INTERVAL: Duration = Duration::from_millis(400);
+    // Keystroke intervals at or below this are colored fully fast (~120 wpm)
+    pub const FAST_INTERVAL: Duration = Duration::from_millis(100);
+    // Most extra characters that can be typed past the end of a word
+    pub const EXTRA_CHAR_BOUNDARY: usize = 5;
 
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub enum TypedState {
         Mistype,
         Untyped,
@@ -20,57 +42,312 @@ pub mod toqst {
         MistypeExtra,
     }
 
+    /// Whether a finished word was typed without any mistakes
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Correctness {
+        Perfect,
+        Imperfect,
+    }
+
     #[derive(Debug, Clone)]
     pub struct StyledWord {
         pub chars: Vec<StyledChar>, // A collection of chars that make up the word
         pub og_len: usize,          // Original length of the chars array
     }
 
+    /// The style of each typed state
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Theme {
+        pub untyped: Style,
+        pub correct: Style,
+        pub mistype: Style,
+        pub mistype_extra: Style,
+        pub caret: Style, // Drawn over the character under the caret, no color by default
+    }
+
+    impl Default for Theme {
+        fn default() -> Self {
+            Self {
+                untyped: Style::new().fg(UNTYPED_COLOR),
+                correct: Style::new().fg(CORRECT_COLOR),
+                mistype: Style::new().fg(MISTYPE_COLOR),
+                mistype_extra: Style::new()
+                    .fg(MISTYPE_EXTRA_COLOR)
+                    .add_modifier(MISTYPE_EXTRA_MODIFIER),
+                caret: Style::new(),
+            }
+        }
+    }
+
+    impl Theme {
+        pub fn style(&self, state: TypedState) -> Style {
+            match state {
+                TypedState::Mistype => self.mistype,
+                TypedState::Untyped => self.untyped,
+                TypedState::Correct => self.correct,
+                TypedState::MistypeExtra => self.mistype_extra,
+            }
+        }
+    }
+
+    /// The colors a terminal can draw
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    pub enum ColorSupport {
+        Basic,     // The 16 named colors
+        Indexed,   // The 256 color palette
+        TrueColor, // Any RGB color
+    }
+
+    impl ColorSupport {
+        /// Guess the color support of the terminal from COLORTERM and TERM
+        pub fn detect() -> Self {
+            Self::from_env(
+                env::var("COLORTERM").ok().as_deref(),
+                env::var("TERM").ok().as_deref(),
+            )
+        }
+
+        pub fn from_env(colorterm: Option<&str>, term: Option<&str>) -> Self {
+            if matches!(colorterm, Some("truecolor" | "24bit")) {
+                ColorSupport::TrueColor
+            } else if term.is_some_and(|term| term.contains("256color")) {
+                ColorSupport::Indexed
+            } else {
+                ColorSupport::Basic
+            }
+        }
+
+        /// The value --colors takes for this support
+        pub fn label(self) -> &'static str {
+            match self {
+                ColorSupport::Basic => "16",
+                ColorSupport::Indexed => "256",
+                ColorSupport::TrueColor => "truecolor",
+            }
+        }
+    }
+
+    impl FromStr for ColorSupport {
+        type Err = color_eyre::Report;
+
+        fn from_str(support: &str) -> color_eyre::Result<Self> {
+            match support {
+                "16" => Ok(ColorSupport::Basic),
+                "256" => Ok(ColorSupport::Indexed),
+                "truecolor" => Ok(ColorSupport::TrueColor),
+                _ => bail!("Unknown color support: {support}"),
+            }
+        }
+    }
+
+    // The 16 named colors with the RGB values of the xterm defaults, in palette order
+    const BASIC_COLORS: [(Color, (u8, u8, u8)); 16] = [
+        (Color::Black, (0, 0, 0)),
+        (Color::Red, (128, 0, 0)),
+        (Color::Green, (0, 128, 0)),
+        (Color::Yellow, (128, 128, 0)),
+        (Color::Blue, (0, 0, 128)),
+        (Color::Magenta, (128, 0, 128)),
+        (Color::Cyan, (0, 128, 128)),
+        (Color::Gray, (192, 192, 192)),
+        (Color::DarkGray, (128, 128, 128)),
+        (Color::LightRed, (255, 0, 0)),
+        (Color::LightGreen, (0, 255, 0)),
+        (Color::LightYellow, (255, 255, 0)),
+        (Color::LightBlue, (0, 0, 255)),
+        (Color::LightMagenta, (255, 0, 255)),
+        (Color::LightCyan, (0, 255, 255)),
+        (Color::White, (255, 255, 255)),
+    ];
+    // Levels of each channel in the 6x6x6 color cube of the 256 color palette
+    const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+    /// RGB value of a color from the 256 color palette
+    fn indexed_rgb(idx: u8) -> (u8, u8, u8) {
+        match idx {
+            0..16 => BASIC_COLORS[idx as usize].1,
+            16..232 => {
+                let idx = idx - 16;
+                (
+                    CUBE_LEVELS[(idx / 36) as usize],
+                    CUBE_LEVELS[(idx / 6 % 6) as usize],
+                    CUBE_LEVELS[(idx % 6) as usize],
+                )
+            }
+            _ => {
+                let level = 8 + 10 * (idx - 232);
+                (level, level, level)
+            }
+        }
+    }
+
+    /// The named color closest to the RGB value
+    fn nearest_basic(rgb: (u8, u8, u8)) -> Color {
+        let distance = |(r, g, b): (u8, u8, u8)| {
+            let channel = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+            channel(r, rgb.0) + channel(g, rgb.1) + channel(b, rgb.2)
+        };
+        BASIC_COLORS
+            .iter()
+            .min_by_key(|(_, basic)| distance(*basic))
+            .map_or(Color::Reset, |(color, _)| *color)
+    }
+
+    /// The color in the 6x6x6 cube of the 256 color palette closest to the RGB value
+    fn nearest_indexed((r, g, b): (u8, u8, u8)) -> Color {
+        let level = |value: u8| {
+            (0..CUBE_LEVELS.len())
+                .min_by_key(|&level| (CUBE_LEVELS[level] as i32 - value as i32).abs())
+                .unwrap_or(0) as u8
+        };
+        Color::Indexed(16 + 36 * level(r) + 6 * level(g) + level(b))
+    }
+
+    /// The closest color the terminal can draw
+    pub fn downgrade_color(color: Color, support: ColorSupport) -> Color {
+        match (color, support) {
+            (Color::Rgb(r, g, b), ColorSupport::Indexed) => nearest_indexed((r, g, b)),
+            (Color::Rgb(r, g, b), ColorSupport::Basic) => nearest_basic((r, g, b)),
+            (Color::Indexed(idx), ColorSupport::Basic) => nearest_basic(indexed_rgb(idx)),
+            _ => color,
+        }
+    }
+
+    fn downgrade_style(style: Style, support: ColorSupport) -> Style {
+        Style {
+            fg: style.fg.map(|color| downgrade_color(color, support)),
+            bg: style.bg.map(|color| downgrade_color(color, support)),
+            ..style
+        }
+    }
+
+    /// The theme with every color the terminal can not draw swapped for the closest one it can
+    pub fn downgrade_theme(theme: Theme, support: ColorSupport) -> Theme {
+        Theme {
+            untyped: downgrade_style(theme.untyped, support),
+            correct: downgrade_style(theme.correct, support),
+            mistype: downgrade_style(theme.mistype, support),
+            mistype_extra: downgrade_style(theme.mistype_extra, support),
+            caret: downgrade_style(theme.caret, support),
+        }
+    }
+
     /// A Character that can be styled for TUI output
     /// Abstraction that Users type StyledChar (not char)
+    /// The style is resolved from the theme when the character is drawn
     #[derive(Debug, Clone)]
     pub struct StyledChar {
         char: char,
-        style: Style,
+        state: TypedState,
+        typed_at: Option<Instant>, // When the character was last typed. None while untyped
+        speed_color: Option<Color>, // Replaces the correct color when coloring by typing speed
     }
 
     impl StyledChar {
         pub fn new(ch: char) -> Self {
             Self {
                 char: ch,
-                style: Style::new().fg(UNTYPED_COLOR),
+                state: TypedState::Untyped,
+                typed_at: None,
+                speed_color: None,
             }
         }
         // Create a Styled Character with a mistype connotation
         pub fn new_bad_char(ch: char) -> Self {
             Self {
                 char: ch,
-                style: Style::new().fg(MISTYPE_EXTRA_COLOR),
+                state: TypedState::MistypeExtra,
+                typed_at: Some(Instant::now()),
+                speed_color: None,
             }
         }
 
         // Switch the Styled State of a Styled Char
         pub fn switch_typed_state(&mut self, state: TypedState) {
-            let color: Color;
-            match state {
-                TypedState::Mistype => color = MISTYPE_COLOR,
-                TypedState::Untyped => color = UNTYPED_COLOR,
-                TypedState::Correct => color = CORRECT_COLOR,
-                TypedState::MistypeExtra => color = MISTYPE_EXTRA_COLOR,
+            self.state = state;
+            self.speed_color = None;
+            self.typed_at = match state {
+                TypedState::Untyped => None,
+                _ => Some(Instant::now()),
+            };
+        }
+
+        /// Mark the character as correct, colored by how long the user took to type it
+        pub fn switch_to_timed_correct(
+            &mut self,
+            interval: Duration,
+            theme: &Theme,
+            support: ColorSupport,
+        ) {
+            self.switch_typed_state(TypedState::Correct);
+            let color = speed_color(interval, theme.correct.fg);
+            self.speed_color = Some(downgrade_color(color, support));
+        }
+
+        pub fn style(&self, theme: &Theme) -> Style {
+            let style = theme.style(self.state);
+            match self.speed_color {
+                Some(color) => style.fg(color),
+                None => style,
             }
-            self.style = self.style.fg(color);
+        }
+
+        /// The style of the character when the caret is on it
+        /// A mistake keeps its own color under a colored caret so the error still shows
+        pub fn caret_style(&self, theme: &Theme, mut caret: Style) -> Style {
+            if matches!(self.state, TypedState::Mistype | TypedState::MistypeExtra) {
+                caret.fg = None;
+            }
+            self.style(theme).patch(caret)
         }
 
         pub fn get_char_data(&self) -> char {
             self.char
         }
+
+        pub fn get_typed_state(&self) -> TypedState {
+            self.state
+        }
+
+        /// Whether the character is a mistake typed less than `grace` ago
+        pub fn is_fresh_mistake(&self, grace: Duration) -> bool {
+            let mistake = matches!(self.state, TypedState::Mistype | TypedState::MistypeExtra);
+            mistake
+                && self
+                    .typed_at
+                    .is_some_and(|typed_at| typed_at.elapsed() < grace)
+        }
+    }
+
+    /// Map the time between two keystrokes onto a color for a correctly typed character
+    ///
+    /// Intervals at or above SLOW_INTERVAL get the base correct color and intervals at or below
+    /// FAST_INTERVAL get FAST_CORRECT_RGB. Anything in between is linearly interpolated
+    /// channel by channel, so the color shifts toward cyan as the typist speeds up
+    /// A named base color has no exact RGB value, so SLOW_CORRECT_RGB is used in its place
+    pub fn speed_color(interval: Duration, base: Option<Color>) -> Color {
+        let interval = interval.clamp(FAST_INTERVAL, SLOW_INTERVAL);
+        let speed = (SLOW_INTERVAL - interval).as_secs_f64()
+            / (SLOW_INTERVAL - FAST_INTERVAL).as_secs_f64();
+        let lerp = |slow: u8, fast: u8| (slow as f64 + (fast as f64 - slow as f64) * speed) as u8;
+        let (slow_r, slow_g, slow_b) = match base {
+            Some(Color::Rgb(r, g, b)) => (r, g, b),
+            Some(Color::Indexed(idx)) => indexed_rgb(idx),
+            _ => SLOW_CORRECT_RGB,
+        };
+        let (fast_r, fast_g, fast_b) = FAST_CORRECT_RGB;
+        Color::Rgb(
+            lerp(slow_r, fast_r),
+            lerp(slow_g, fast_g),
+            lerp(slow_b, fast_b),
+        )
     }
 
     impl StyledWord {
         pub fn from_chars(chars: Vec<char>) -> Self {
             Self {
                 og_len: chars.len(),
-                chars: chars.into_iter().map(|ch| StyledChar::new(ch)).collect(),
+                chars: chars.into_iter().map(StyledChar::new).collect(),
             }
         }
 
@@ -78,31 +355,79 @@ pub mod toqst {
             StyledWord::from_chars(chars.chars().collect())
         }
 
-        pub fn get_styled_word(&self) -> Vec<Span<'_>> {
+        /// A word is only perfect when every original character was typed correctly and no
+        /// extra characters were added
+        pub fn correctness(&self) -> Correctness {
+            let all_correct = self.chars.iter().all(|ch| ch.state == TypedState::Correct);
+            if all_correct && self.chars.len() == self.og_len {
+                Correctness::Perfect
+            } else {
+                Correctness::Imperfect
+            }
+        }
+
+        pub fn get_styled_word(&self, theme: &Theme) -> Vec<Span<'_>> {
             self.chars
                 .iter()
-                .map(|char| Span::styled(String::from(char.char), char.style))
+                .map(|char| Span::styled(String::from(char.char), char.style(theme)))
                 .collect()
         }
 
-        /// Get the Styled Representation of a word with a modifier added to one of the
-        /// characters in the word
+        /// Get the Styled Representation of a word with the caret on one of the characters in
+        /// the word
         /// It is assumed that the idx is within bounds of the word
-        pub fn get_styled_with_modifier(&self, idx: usize, modifier: Modifier) -> Vec<Span<'_>> {
+        pub fn get_styled_with_caret(
+            &self,
+            idx: usize,
+            caret: Style,
+            theme: &Theme,
+        ) -> Vec<Span<'_>> {
             self.chars
                 .iter()
                 .enumerate()
-                .map(|(iter_idx, StyledChar { char, style })| {
+                .map(|(iter_idx, char)| {
                     let style = if iter_idx == idx {
-                        style.add_modifier(modifier)
+                        char.caret_style(theme, caret)
                     } else {
-                        *style
+                        char.style(theme)
                     };
-                    Span::styled(String::from(*char), style)
+                    Span::styled(String::from(char.char), style)
                 })
                 .collect()
         }
 
+        /// Type a character at the index and return the new state of the character there
+        /// Past the end of the word the character is added as an extra, up to
+        /// EXTRA_CHAR_BOUNDARY extras. None when the character is dropped for being over the limit
+        pub fn type_char(&mut self, idx: usize, typed: char) -> Option<TypedState> {
+            if let Some(ch) = self.get_mut_ch(idx) {
+                let state = if ch.char == typed {
+                    TypedState::Correct
+                } else {
+                    TypedState::Mistype
+                };
+                ch.switch_typed_state(state);
+                return Some(state);
+            }
+            if self.chars.len() - self.og_len >= EXTRA_CHAR_BOUNDARY {
+                return None;
+            }
+            self.append_char(StyledChar::new_bad_char(typed));
+            Some(TypedState::MistypeExtra)
+        }
+
+        /// Reset the word and type the whole string into it from the start
+        /// Used to replay what was typed into a word
+        pub fn type_against(&mut self, typed: &str) {
+            self.chars.truncate(self.og_len);
+            for ch in &mut self.chars {
+                ch.switch_typed_state(TypedState::Untyped);
+            }
+            for (idx, ch) in typed.chars().enumerate() {
+                self.type_char(idx, ch);
+            }
+        }
+
         pub fn append_char(&mut self, ch: StyledChar) {
             self.chars.push(ch)
         }
@@ -111,4 +436,960 @@ pub mod toqst {
             self.chars.get_mut(index)
         }
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn states(word: &StyledWord) -> Vec<TypedState> {
+            word.chars.iter().map(StyledChar::get_typed_state).collect()
+        }
+
+        #[test]
+        fn type_against_exact_match() {
+            let mut word = StyledWord::from_string("cat");
+            word.type_against("cat");
+            assert_eq!(states(&word), vec![TypedState::Correct; 3]);
+            assert_eq!(word.correctness(), Correctness::Perfect);
+        }
+
+        #[test]
+        fn type_against_substitution() {
+            let mut word = StyledWord::from_string("cat");
+            word.type_against("cut");
+            assert_eq!(
+                states(&word),
+                vec![
+                    TypedState::Correct,
+                    TypedState::Mistype,
+                    TypedState::Correct
+                ]
+            );
+            assert_eq!(word.correctness(), Correctness::Imperfect);
+        }
+
+        #[test]
+        fn type_against_overflow_stops_at_the_boundary() {
+            let mut word = StyledWord::from_string("cat");
+            let typed = format!("cat{}", "s".repeat(EXTRA_CHAR_BOUNDARY + 2));
+            word.type_against(&typed);
+            assert_eq!(word.chars.len(), word.og_len + EXTRA_CHAR_BOUNDARY);
+            assert!(word.chars[word.og_len..]
+                .iter()
+                .all(|ch| ch.get_typed_state() == TypedState::MistypeExtra));
+        }
+
+        #[test]
+        fn type_against_starts_over() {
+            let mut word = StyledWord::from_string("cat");
+            word.type_against("catss");
+            word.type_against("c");
+            assert_eq!(word.chars.len(), word.og_len);
+            assert_eq!(
+                states(&word),
+                vec![
+                    TypedState::Correct,
+                    TypedState::Untyped,
+                    TypedState::Untyped
+                ]
+            );
+        }
+
+        #[test]
+        fn downgrade_theme_fits_truecolor_to_16_colors() {
+            let theme = Theme {
+                mistype: Style::new().fg(Color::Rgb(150, 0, 0)),
+                correct: Style::new().fg(Color::Indexed(46)),
+                ..Theme::default()
+            };
+            let fitted = downgrade_theme(theme, ColorSupport::Basic);
+            assert_eq!(fitted.mistype.fg, Some(Color::Red));
+            assert_eq!(fitted.correct.fg, Some(Color::LightGreen));
+            assert_eq!(downgrade_theme(theme, ColorSupport::TrueColor), theme);
+        }
+
+        #[test]
+        fn speed_color_starts_from_the_correct_color() {
+            let base = Some(Color::Rgb(200, 100, 0));
+            assert_eq!(speed_color(SLOW_INTERVAL, base), Color::Rgb(200, 100, 0));
+            let (r, g, b) = FAST_CORRECT_RGB;
+            assert_eq!(speed_color(FAST_INTERVAL, base), Color::Rgb(r, g, b));
+            // The default green has no exact RGB value
+            let (r, g, b) = SLOW_CORRECT_RGB;
+            assert_eq!(
+                speed_color(SLOW_INTERVAL, Some(CORRECT_COLOR)),
+                Color::Rgb(r, g, b)
+            );
+        }
+
+        #[test]
+        fn speed_color_is_fitted_to_the_terminal() {
+            let mut ch = StyledChar::new('a');
+            ch.switch_to_timed_correct(SLOW_INTERVAL, &Theme::default(), ColorSupport::Basic);
+            let fg = ch.style(&Theme::default()).fg;
+            assert!(!matches!(fg, Some(Color::Rgb(..) | Color::Indexed(_))));
+        }
+    }
+}
+
+pub mod words {
+    use std::{
+        collections::HashSet,
+        fs::File,
+        io::{BufRead, BufReader},
+        path::Path,
+    };
+
+    use color_eyre::{
+        eyre::{bail, WrapErr},
+        Result,
+    };
+
+    /// Load several word files into one pool, in the order the files are given
+    pub fn load_word_files(paths: &[impl AsRef<Path>], allow_tabs: bool) -> Result<Vec<String>> {
+        let mut words = Vec::new();
+        for path in paths {
+            words.extend(load_words(path, allow_tabs)?);
+        }
+        Ok(words)
+    }
+
+    /// Built in practice sequences by name, typed in order instead of words from a word file
+    pub const DRILLS: &[(&str, &str)] = &[
+        (
+            "home-row",
+            "asdf jkl; asdf jkl; fdsa ;lkj fdsa ;lkj as df jk l; sad lad fall",
+        ),
+        (
+            "top-row",
+            "qwer uiop qwer uiop rewq poiu rewq poiu we re ui op try pour",
+        ),
+        (
+            "bottom-row",
+            "zxcv m,./ zxcv m,./ vcxz /.,m vcxz /.,m zx cv m, ./ zinc mix",
+        ),
+        ("alphabet", "abcdefghijklmnopqrstuvwxyz"),
+        (
+            "numbers",
+            "1234 5678 90 1234 5678 90 12 34 56 78 90 1357 2468",
+        ),
+    ];
+
+    /// The text of the drill with the given name
+    pub fn drill_text(name: &str) -> Option<&'static str> {
+        DRILLS
+            .iter()
+            .find(|(drill, _)| *drill == name)
+            .map(|(_, text)| *text)
+    }
+
+    /// Remove repeated words from the pool, keeping the first time each word appears so a
+    /// frequency ordered list stays in order
+    pub fn dedup_words(words: Vec<String>, ignore_case: bool) -> Vec<String> {
+        let mut seen = HashSet::new();
+        words
+            .into_iter()
+            .filter(|word| {
+                if ignore_case {
+                    seen.insert(word.to_lowercase())
+                } else {
+                    seen.insert(word.clone())
+                }
+            })
+            .collect()
+    }
+
+    /// Which kinds of words to remove from the word pool
+    #[derive(Debug, Default, Clone, Copy)]
+    pub struct WordFilter {
+        pub no_apostrophe: bool, // Remove words containing '
+        pub no_hyphen: bool,     // Remove words containing -
+        pub alpha_only: bool,    // Remove words with anything other than letters
+    }
+
+    impl WordFilter {
+        pub fn keeps(&self, word: &str) -> bool {
+            !(self.no_apostrophe && word.contains('\''))
+                && !(self.no_hyphen && word.contains('-'))
+                && !(self.alpha_only && !word.chars().all(char::is_alphabetic))
+        }
+    }
+
+    /// Remove every word the filter does not keep
+    /// It is an error for the filter to remove every word since there would be nothing to type
+    pub fn filter_words(words: Vec<String>, filter: &WordFilter) -> Result<Vec<String>> {
+        let words: Vec<_> = words
+            .into_iter()
+            .filter(|word| filter.keeps(word))
+            .collect();
+        if words.is_empty() {
+            bail!("No words are left in the word pool after filtering");
+        }
+        Ok(words)
+    }
+
+    /// Read a word list file with one word per line
+    ///
+    /// Each line is trimmed and blank lines are skipped
+    /// A line that can not be read (e.g. invalid UTF-8) is an error naming the line number,
+    /// rather than being silently dropped
+    /// Control characters would corrupt the terminal so they are stripped from every line.
+    /// Tabs can be kept for text that needs them
+    pub fn load_words(path: impl AsRef<Path>, allow_tabs: bool) -> Result<Vec<String>> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .wrap_err_with(|| format!("Failed to open word file {}", path.display()))?;
+        let mut words = Vec::new();
+        for (idx, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.wrap_err_with(|| {
+                format!("Failed to read line {} of {}", idx + 1, path.display())
+            })?;
+            let line: String = line
+                .chars()
+                .filter(|ch| !ch.is_control() || (allow_tabs && *ch == '\t'))
+                .collect();
+            // Kept tabs are part of the word, even at its start or end
+            let word = line.trim_matches(|ch: char| ch.is_whitespace() && ch != '\t');
+            if !word.is_empty() {
+                words.push(word.to_string());
+            }
+        }
+        Ok(words)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::{env, fs, path::PathBuf, process};
+
+        use super::*;
+
+        /// Write a word file to the temporary directory, named so parallel tests do not clash
+        fn word_file(name: &str, contents: &[u8]) -> PathBuf {
+            let path = env::temp_dir().join(format!("toqst-test-{}-{name}", process::id()));
+            fs::write(&path, contents).unwrap();
+            path
+        }
+
+        #[test]
+        fn load_words_strips_tabs_and_nuls() {
+            let path = word_file("control", b"ta\tb\0le\nplain\n");
+            assert_eq!(load_words(&path, false).unwrap(), vec!["table", "plain"]);
+            fs::remove_file(path).unwrap();
+        }
+
+        #[test]
+        fn load_words_keeps_tabs_when_allowed() {
+            let path = word_file("tabs", b"\tindented\0\nplain\n");
+            assert_eq!(
+                load_words(&path, true).unwrap(),
+                vec!["\tindented", "plain"]
+            );
+            fs::remove_file(path).unwrap();
+        }
+
+        #[test]
+        fn load_words_names_the_line_with_invalid_utf8() {
+            let path = word_file("invalid", b"fine\nbad\xff\n");
+            let err = load_words(&path, false).unwrap_err();
+            assert!(err.to_string().starts_with("Failed to read line 2 of"));
+            fs::remove_file(path).unwrap();
+        }
+
+        fn pool() -> Vec<String> {
+            ["don't", "well-known", "abc123", "plain"]
+                .map(String::from)
+                .to_vec()
+        }
+
+        #[test]
+        fn filter_words_removes_apostrophes() {
+            let filter = WordFilter {
+                no_apostrophe: true,
+                ..WordFilter::default()
+            };
+            assert_eq!(
+                filter_words(pool(), &filter).unwrap(),
+                vec!["well-known", "abc123", "plain"]
+            );
+        }
+
+        #[test]
+        fn filter_words_removes_hyphens() {
+            let filter = WordFilter {
+                no_hyphen: true,
+                ..WordFilter::default()
+            };
+            assert_eq!(
+                filter_words(pool(), &filter).unwrap(),
+                vec!["don't", "abc123", "plain"]
+            );
+        }
+
+        #[test]
+        fn filter_words_keeps_only_letters() {
+            let filter = WordFilter {
+                alpha_only: true,
+                ..WordFilter::default()
+            };
+            assert_eq!(filter_words(pool(), &filter).unwrap(), vec!["plain"]);
+        }
+
+        #[test]
+        fn filter_words_fails_on_an_empty_pool() {
+            let filter = WordFilter {
+                alpha_only: true,
+                ..WordFilter::default()
+            };
+            assert!(filter_words(vec![String::from("abc123")], &filter).is_err());
+        }
+
+        #[test]
+        fn load_word_files_merges_in_order() {
+            let first = word_file("first", b"one\ntwo\n");
+            let second = word_file("second", b"three\n");
+            assert_eq!(
+                load_word_files(&[&second, &first], false).unwrap(),
+                vec!["three", "one", "two"]
+            );
+            fs::remove_file(first).unwrap();
+            fs::remove_file(second).unwrap();
+        }
+
+        #[test]
+        fn load_word_files_names_the_missing_file() {
+            let present = word_file("present", b"one\n");
+            let missing = env::temp_dir().join("toqst-test-missing-words.txt");
+            let err = load_word_files(&[&present, &missing], false).unwrap_err();
+            assert!(err.to_string().contains(&missing.display().to_string()));
+            fs::remove_file(present).unwrap();
+        }
+
+        fn repeated() -> Vec<String> {
+            ["the", "of", "The", "and", "of", "THE"]
+                .map(String::from)
+                .to_vec()
+        }
+
+        #[test]
+        fn dedup_words_keeps_the_first_of_each() {
+            assert_eq!(
+                dedup_words(repeated(), false),
+                vec!["the", "of", "The", "and", "THE"]
+            );
+        }
+
+        #[test]
+        fn dedup_words_can_ignore_case() {
+            assert_eq!(dedup_words(repeated(), true), vec!["the", "of", "and"]);
+        }
+    }
+}
+
+pub mod stats {
+    use std::time::{Duration, Instant};
+
+    // Standard number of characters that make up a "word" when measuring typing speed
+    pub const CHARS_PER_WORD: f64 = 5.0;
+
+    /// A count with a comma between every group of three digits, like 1,234
+    pub fn fmt_count(n: usize) -> String {
+        let digits = n.to_string();
+        let mut formatted = String::with_capacity(digits.len() + digits.len() / 3);
+        for (idx, digit) in digits.chars().enumerate() {
+            if idx > 0 && (digits.len() - idx).is_multiple_of(3) {
+                formatted.push(',');
+            }
+            formatted.push(digit);
+        }
+        formatted
+    }
+
+    /// Words per minute using the standard five characters per word
+    /// By default only the characters of the words are counted and the spaces between them are
+    /// not. Callers that want to count spaces include them in `chars`
+    pub fn wpm(chars: usize, elapsed: Duration) -> f64 {
+        let minutes = elapsed.as_secs_f64() / 60.0;
+        if minutes == 0.0 {
+            return 0.0;
+        }
+        chars as f64 / CHARS_PER_WORD / minutes
+    }
+
+    /// Highest wpm over any window of the given length, from when each correct character was
+    /// typed in order
+    /// None when the characters were typed over less time than the window, since no full window
+    /// fits. Callers fall back to the wpm of the whole test
+    pub fn peak_wpm(typed_at: &[Instant], window: Duration) -> Option<f64> {
+        let (first, last) = (typed_at.first()?, typed_at.last()?);
+        if *last - *first < window {
+            return None;
+        }
+        let mut start = 0;
+        let mut peak = 0;
+        for (end, &time) in typed_at.iter().enumerate() {
+            while time - typed_at[start] > window {
+                start += 1;
+            }
+            peak = peak.max(end - start + 1);
+        }
+        Some(wpm(peak, window))
+    }
+
+    /// Keystrokes per second, right or wrong
+    pub fn kps(keystrokes: usize, elapsed: Duration) -> f64 {
+        let seconds = elapsed.as_secs_f64();
+        if seconds == 0.0 {
+            return 0.0;
+        }
+        keystrokes as f64 / seconds
+    }
+
+    /// Percentage of keystrokes that were not mistakes
+    pub fn accuracy(mistakes: usize, keystrokes: usize) -> f64 {
+        if keystrokes == 0 {
+            return 100.0;
+        }
+        keystrokes.saturating_sub(mistakes) as f64 / keystrokes as f64 * 100.0
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn kps_is_keystrokes_over_seconds() {
+            assert_eq!(kps(30, Duration::from_secs(10)), 3.0);
+            assert_eq!(kps(5, Duration::from_millis(2500)), 2.0);
+        }
+
+        /// One character a second for ten seconds, then ten characters within a second
+        fn burst_series() -> Vec<Instant> {
+            let start = Instant::now();
+            let steady = (0..10).map(|second| start + Duration::from_secs(second));
+            let burst = (0..10).map(|tenth| start + Duration::from_millis(10_000 + tenth * 100));
+            steady.chain(burst).collect()
+        }
+
+        #[test]
+        fn peak_wpm_finds_the_busiest_window() {
+            // The window ending on the last character holds 4 steady characters and the burst
+            let peak = peak_wpm(&burst_series(), Duration::from_secs(5)).unwrap();
+            assert!((peak - wpm(14, Duration::from_secs(5))).abs() < 1e-9);
+        }
+
+        #[test]
+        fn peak_wpm_needs_a_full_window() {
+            assert_eq!(peak_wpm(&burst_series(), Duration::from_secs(20)), None);
+            assert_eq!(peak_wpm(&[], Duration::from_secs(5)), None);
+        }
+
+        #[test]
+        fn kps_is_zero_before_typing() {
+            assert_eq!(kps(0, Duration::ZERO), 0.0);
+            assert_eq!(kps(10, Duration::ZERO), 0.0);
+        }
+
+        #[test]
+        fn fmt_count_groups_thousands() {
+            assert_eq!(fmt_count(0), "0");
+            assert_eq!(fmt_count(999), "999");
+            assert_eq!(fmt_count(1000), "1,000");
+            assert_eq!(fmt_count(1_234_567), "1,234,567");
+        }
+    }
+}
+
+pub mod history {
+    use std::{
+        fs::{self, OpenOptions},
+        io::{ErrorKind, Write},
+        path::Path,
+        time::Duration,
+    };
+
+    use chrono::{DateTime, Days, Local, NaiveDate};
+    use color_eyre::{
+        eyre::{eyre, WrapErr},
+        Result,
+    };
+
+    /// The outcome of a single finished typing test
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct RunResult {
+        pub timestamp: DateTime<Local>, // When the test was started
+        pub duration: Duration,         // How long the user actually spent typing
+        pub wpm: f64,
+        pub accuracy: f64, // Percentage of keystrokes that were not mistakes
+    }
+
+    impl RunResult {
+        /// A single line of the history file: `timestamp,wpm,accuracy,duration in seconds`
+        pub fn to_history_line(&self) -> String {
+            format!(
+                "{},{:.2},{:.2},{:.2}",
+                self.timestamp.to_rfc3339(),
+                self.wpm,
+                self.accuracy,
+                self.duration.as_secs_f64()
+            )
+        }
+
+        /// Lines written before the duration was recorded have a duration of zero
+        pub fn from_history_line(line: &str) -> Result<Self> {
+            let mut fields = line.split(',');
+            let mut next_field = |name: &str| {
+                fields
+                    .next()
+                    .ok_or_else(|| eyre!("Missing {name} in history line: {line}"))
+            };
+            let timestamp = DateTime::parse_from_rfc3339(next_field("timestamp")?)
+                .wrap_err("Invalid timestamp")?
+                .with_timezone(&Local);
+            let wpm = next_field("wpm")?.parse().wrap_err("Invalid wpm")?;
+            let accuracy = next_field("accuracy")?
+                .parse()
+                .wrap_err("Invalid accuracy")?;
+            let duration = match fields.next() {
+                Some(seconds) => {
+                    Duration::try_from_secs_f64(seconds.parse().wrap_err("Invalid duration")?)
+                        .wrap_err("Invalid duration")?
+                }
+                None => Duration::ZERO,
+            };
+            Ok(Self {
+                timestamp,
+                duration,
+                wpm,
+                accuracy,
+            })
+        }
+    }
+
+    /// Record a finished test at the end of the history file, creating the file if needed
+    pub fn append_history(path: impl AsRef<Path>, result: &RunResult) -> Result<()> {
+        let path = path.as_ref();
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .wrap_err_with(|| format!("Failed to open history file {}", path.display()))?;
+        writeln!(file, "{}", result.to_history_line())
+            .wrap_err_with(|| format!("Failed to write history file {}", path.display()))
+    }
+
+    /// Read every test recorded in the history file, oldest first
+    /// A missing history file means no tests have been taken yet
+    pub fn load_history(path: impl AsRef<Path>) -> Result<Vec<RunResult>> {
+        let path = path.as_ref();
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => {
+                return Err(err)
+                    .wrap_err_with(|| format!("Failed to read history file {}", path.display()))
+            }
+        };
+        contents
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(idx, line)| {
+                RunResult::from_history_line(line).wrap_err_with(|| {
+                    format!("Failed to parse line {} of {}", idx + 1, path.display())
+                })
+            })
+            .collect()
+    }
+
+    /// How a test compares to the one taken before it
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Comparison {
+        pub wpm: f64,      // Change in wpm, positive when faster
+        pub accuracy: f64, // Change in accuracy in percentage points
+    }
+
+    pub fn compare_to_previous(prev: &RunResult, cur: &RunResult) -> Comparison {
+        Comparison {
+            wpm: cur.wpm - prev.wpm,
+            accuracy: cur.accuracy - prev.accuracy,
+        }
+    }
+
+    /// Summary of every test the user has taken
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Summary {
+        pub tests: usize,
+        pub average_wpm: f64,
+        pub best_wpm: f64,
+        pub streak: usize, // Consecutive days up to today with at least one test
+    }
+
+    pub fn summarize(history: &[RunResult], today: NaiveDate) -> Summary {
+        let tests = history.len();
+        let average_wpm = if tests == 0 {
+            0.0
+        } else {
+            history.iter().map(|run| run.wpm).sum::<f64>() / tests as f64
+        };
+        let best_wpm = history.iter().map(|run| run.wpm).fold(0.0, f64::max);
+        Summary {
+            tests,
+            average_wpm,
+            best_wpm,
+            streak: streak(history, today),
+        }
+    }
+
+    /// Count the consecutive days with a test, ending today
+    /// A streak is not broken until a whole day passes, so when nothing has been typed today
+    /// the streak is counted from yesterday
+    fn streak(history: &[RunResult], today: NaiveDate) -> usize {
+        let has_test = |day: NaiveDate| history.iter().any(|run| run.timestamp.date_naive() == day);
+        let mut day = if has_test(today) {
+            today
+        } else {
+            match today.checked_sub_days(Days::new(1)) {
+                Some(yesterday) => yesterday,
+                None => return 0,
+            }
+        };
+        let mut streak = 0;
+        while has_test(day) {
+            streak += 1;
+            match day.checked_sub_days(Days::new(1)) {
+                Some(previous) => day = previous,
+                None => break,
+            }
+        }
+        streak
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use chrono::TimeZone;
+
+        use super::*;
+
+        fn result(wpm: f64, accuracy: f64) -> RunResult {
+            RunResult {
+                timestamp: Local::now(),
+                duration: Duration::from_secs(30),
+                wpm,
+                accuracy,
+            }
+        }
+
+        #[test]
+        fn compare_to_previous_is_current_minus_previous() {
+            let comparison = compare_to_previous(&result(60.0, 95.0), &result(64.0, 94.0));
+            assert_eq!(
+                comparison,
+                Comparison {
+                    wpm: 4.0,
+                    accuracy: -1.0
+                }
+            );
+        }
+
+        /// Tests on the 8th, 9th and twice on the 10th of March, with a gap before the 8th
+        fn fixture() -> Vec<RunResult> {
+            [(5, 40.0), (8, 50.0), (9, 60.0), (10, 70.0), (10, 80.0)]
+                .into_iter()
+                .map(|(day, wpm)| RunResult {
+                    timestamp: Local.with_ymd_and_hms(2024, 3, day, 12, 0, 0).unwrap(),
+                    ..result(wpm, 100.0)
+                })
+                .collect()
+        }
+
+        fn march(day: u32) -> NaiveDate {
+            NaiveDate::from_ymd_opt(2024, 3, day).unwrap()
+        }
+
+        #[test]
+        fn summarize_averages_every_test() {
+            let summary = summarize(&fixture(), march(10));
+            assert_eq!(summary.tests, 5);
+            assert_eq!(summary.average_wpm, 60.0);
+            assert_eq!(summary.best_wpm, 80.0);
+            assert_eq!(summary.streak, 3);
+        }
+
+        #[test]
+        fn streak_waits_a_day_before_breaking() {
+            assert_eq!(summarize(&fixture(), march(11)).streak, 3);
+            assert_eq!(summarize(&fixture(), march(12)).streak, 0);
+        }
+
+        #[test]
+        fn history_line_round_trips() {
+            let run = result(64.25, 97.5);
+            let line = run.to_history_line();
+            assert_eq!(RunResult::from_history_line(&line).unwrap(), run);
+        }
+
+        #[test]
+        fn old_history_lines_have_no_duration() {
+            let run =
+                RunResult::from_history_line("2024-03-10T12:00:00+00:00,64.25,97.50").unwrap();
+            assert_eq!(run.duration, Duration::ZERO);
+            assert_eq!(run.wpm, 64.25);
+        }
+
+        #[test]
+        fn summarize_empty_history() {
+            let summary = summarize(&[], march(10));
+            assert_eq!(
+                (summary.tests, summary.average_wpm, summary.streak),
+                (0, 0.0, 0)
+            );
+        }
+    }
+}
+
+pub mod export {
+    use std::{fs, path::Path, time::Duration};
+
+    use color_eyre::{eyre::WrapErr, Result};
+
+    use crate::history::RunResult;
+
+    /// How a single word of the test was typed
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct WordDetail {
+        pub target: String, // The word the user was asked to type
+        pub extra: String,  // Characters typed past the end of the word
+        pub perfect: bool,  // Typed without any mistakes
+    }
+
+    /// A finished test along with every word the user reached
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct RunDetail {
+        pub result: RunResult,
+        pub words: Vec<WordDetail>,
+    }
+
+    impl RunDetail {
+        /// The test as a JSON object
+        /// A private export only keeps the aggregate stats, so none of the typed text is shared
+        pub fn to_json(&self, private: bool) -> String {
+            let RunResult {
+                timestamp,
+                duration,
+                wpm,
+                accuracy,
+            } = &self.result;
+            let mut json = format!(
+                "{{\"timestamp\":{},\"duration_secs\":{:.2},\"wpm\":{wpm:.2},\"accuracy\":{accuracy:.2}",
+                json_string(&timestamp.to_rfc3339()),
+                duration.as_secs_f64()
+            );
+            if !private {
+                let words: Vec<_> = self
+                    .words
+                    .iter()
+                    .map(|word| {
+                        format!(
+                            "{{\"target\":{},\"extra\":{},\"perfect\":{}}}",
+                            json_string(&word.target),
+                            json_string(&word.extra),
+                            word.perfect
+                        )
+                    })
+                    .collect();
+                json.push_str(&format!(",\"words\":[{}]", words.join(",")));
+            }
+            json.push('}');
+            json
+        }
+    }
+
+    /// A single keystroke and how long after the previous one it was typed
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct KeyTiming {
+        pub expected: char, // A space when the end of the word was reached
+        pub typed: char,
+        pub correct: bool,
+        pub interval: Option<Duration>, // None for the first keystroke of the test
+    }
+
+    /// Write every keystroke of the test to a JSON file, replacing the file if it exists
+    pub fn write_timing_export(path: impl AsRef<Path>, rows: &[KeyTiming]) -> Result<()> {
+        let path = path.as_ref();
+        let rows: Vec<_> = rows
+            .iter()
+            .map(|row| {
+                let interval = row.interval.map_or(String::from("null"), |interval| {
+                    format!("{:.1}", interval.as_secs_f64() * 1000.0)
+                });
+                format!(
+                    "{{\"expected\":{},\"typed\":{},\"correct\":{},\"interval_ms\":{interval}}}",
+                    json_string(&row.expected.to_string()),
+                    json_string(&row.typed.to_string()),
+                    row.correct
+                )
+            })
+            .collect();
+        fs::write(path, format!("[{}]\n", rows.join(",")))
+            .wrap_err_with(|| format!("Failed to write timing export {}", path.display()))
+    }
+
+    /// Stats of a test that is still being typed, saved periodically in case the app crashes
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ProgressSnapshot {
+        pub elapsed: Duration,    // Time spent typing so far
+        pub chars_typed: usize,   // Every keystroke, right or wrong
+        pub correct_chars: usize, // Characters that are currently typed correctly
+        pub words_done: usize,
+        pub mistakes: usize,
+    }
+
+    impl ProgressSnapshot {
+        pub fn to_json(&self) -> String {
+            format!(
+                "{{\"elapsed_secs\":{:.2},\"chars_typed\":{},\"correct_chars\":{},\"words_done\":{},\"mistakes\":{}}}",
+                self.elapsed.as_secs_f64(),
+                self.chars_typed,
+                self.correct_chars,
+                self.words_done,
+                self.mistakes
+            )
+        }
+    }
+
+    /// Write the test to a JSON file, replacing the file if it exists
+    pub fn write_export(path: impl AsRef<Path>, detail: &RunDetail, private: bool) -> Result<()> {
+        let path = path.as_ref();
+        fs::write(path, detail.to_json(private) + "\n")
+            .wrap_err_with(|| format!("Failed to write export file {}", path.display()))
+    }
+
+    /// Quote and escape a string for JSON
+    fn json_string(text: &str) -> String {
+        let mut quoted = String::with_capacity(text.len() + 2);
+        quoted.push('"');
+        for ch in text.chars() {
+            match ch {
+                '"' => quoted.push_str("\\\""),
+                '\\' => quoted.push_str("\\\\"),
+                '\n' => quoted.push_str("\\n"),
+                '\t' => quoted.push_str("\\t"),
+                ch if ch.is_control() => quoted.push_str(&format!("\\u{:04x}", ch as u32)),
+                ch => quoted.push(ch),
+            }
+        }
+        quoted.push('"');
+        quoted
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use chrono::Local;
+
+        use super::*;
+
+        fn detail() -> RunDetail {
+            RunDetail {
+                result: RunResult {
+                    timestamp: Local::now(),
+                    duration: Duration::from_secs(30),
+                    wpm: 60.0,
+                    accuracy: 95.0,
+                },
+                words: vec![
+                    WordDetail {
+                        target: String::from("secret"),
+                        extra: String::new(),
+                        perfect: true,
+                    },
+                    WordDetail {
+                        target: String::from("quote\""),
+                        extra: String::from("xyz"),
+                        perfect: false,
+                    },
+                ],
+            }
+        }
+
+        #[test]
+        fn private_export_leaves_out_the_words() {
+            let json = detail().to_json(true);
+            assert!(json.contains("\"wpm\":60.00"));
+            assert!(!json.contains("target"));
+            assert!(!json.contains("secret"));
+            assert!(!json.contains("xyz"));
+        }
+
+        #[test]
+        fn export_escapes_the_words() {
+            let json = detail().to_json(false);
+            assert!(json.contains("{\"target\":\"secret\",\"extra\":\"\",\"perfect\":true}"));
+            assert!(json.contains("\"target\":\"quote\\\"\""));
+        }
+    }
+}
+
+pub mod sound {
+    #[cfg(feature = "sound")]
+    use std::time::Duration;
+
+    #[cfg(feature = "sound")]
+    use rodio::{source::SineWave, OutputStream, OutputStreamHandle, Source};
+
+    #[cfg(feature = "sound")]
+    const CLICK_DURATION: Duration = Duration::from_millis(15);
+    #[cfg(feature = "sound")]
+    const CLICK_FREQUENCY: f32 = 1800.0;
+    // Lower and longer so a mistake can be told apart without looking
+    #[cfg(feature = "sound")]
+    const MISTAKE_DURATION: Duration = Duration::from_millis(60);
+    #[cfg(feature = "sound")]
+    const MISTAKE_FREQUENCY: f32 = 220.0;
+    #[cfg(feature = "sound")]
+    const VOLUME: f32 = 0.15;
+
+    /// Whether the binary was built with the `sound` feature
+    pub const AVAILABLE: bool = cfg!(feature = "sound");
+
+    /// Plays a short sound for every keystroke
+    /// Without an audio device every sound is skipped, so typing is never interrupted
+    pub struct Clicker {
+        // The stream stops playing once it is dropped, so it is kept along with its handle
+        #[cfg(feature = "sound")]
+        output: Option<(OutputStream, OutputStreamHandle)>,
+    }
+
+    impl Clicker {
+        pub fn new() -> Self {
+            Self {
+                #[cfg(feature = "sound")]
+                output: OutputStream::try_default().ok(),
+            }
+        }
+
+        /// A click for a correct keystroke and a low tone for a mistake
+        pub fn click(&self, mistake: bool) {
+            #[cfg(feature = "sound")]
+            if let Some((_, handle)) = &self.output {
+                let (frequency, duration) = if mistake {
+                    (MISTAKE_FREQUENCY, MISTAKE_DURATION)
+                } else {
+                    (CLICK_FREQUENCY, CLICK_DURATION)
+                };
+                let tone = SineWave::new(frequency)
+                    .take_duration(duration)
+                    .amplify(VOLUME);
+                // A sound that fails to play is skipped like a missing device
+                let _ = handle.play_raw(tone);
+            }
+            #[cfg(not(feature = "sound"))]
+            let _ = mistake;
+        }
+    }
+
+    impl Default for Clicker {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
 }
@@ -1,70 +1,918 @@
-#![feature(iter_intersperse, file_buffered)]
-
 extern crate chrono;
 extern crate timer;
 
-use std::sync::atomic::{AtomicIsize, Ordering};
+#[cfg(feature = "bench")]
+use std::alloc::{GlobalAlloc, Layout as AllocLayout, System};
+#[cfg(feature = "bench")]
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::{
-    fs::File,
-    io::{self, BufRead},
+    env, fs,
+    io::{self, ErrorKind, Write},
+    iter, mem,
+    path::{Path, PathBuf},
+    str::FromStr,
     thread, time, vec,
 };
 
-use std::sync::Arc;
-
+use chrono::{DateTime, Local, NaiveDate};
 use std::cmp;
-use toqst_typer::toqst::*;
+use toqst_typer::{
+    export::{
+        write_export, write_timing_export, KeyTiming, ProgressSnapshot, RunDetail, WordDetail,
+    },
+    history::{
+        append_history, compare_to_previous, load_history, summarize, Comparison, RunResult,
+    },
+    sound::{self, Clicker},
+    stats::{accuracy, fmt_count, kps, peak_wpm, wpm, CHARS_PER_WORD},
+    toqst::*,
+    words::{dedup_words, drill_text, filter_words, load_word_files, WordFilter, DRILLS},
+};
 
-use color_eyre::Result;
+use color_eyre::{
+    eyre::{bail, eyre, WrapErr},
+    Result,
+};
 use ratatui::{
     buffer::Buffer,
-    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    crossterm::{
+        event::{
+            self, DisableFocusChange, EnableFocusChange, Event, KeyCode, KeyEventKind, KeyModifiers,
+        },
+        execute,
+    },
     layout::{Constraint, Layout, Rect},
-    style::{Modifier, Style, Stylize},
+    style::{Color, Modifier, Style, Stylize},
     text::{Line, Span},
-    widgets::{Block, Paragraph, Widget, Wrap},
+    widgets::{Block, Clear, Paragraph, Widget, Wrap},
     DefaultTerminal, Frame,
 };
 
 use rand::seq::IteratorRandom;
 
-const SPEED_TYPING_TITLE: &'static str = "Toqst's Speed Typing Test";
-const FILE: &'static str = "1000-most-common-words.txt";
+const SPEED_TYPING_TITLE: &str = "Toqst's Speed Typing Test";
+const FILE: &str = "1000-most-common-words.txt";
+const HISTORY_FILE: &str = "toqst-history.csv";
+// Settings of the last test, loaded underneath the command line arguments on the next launch
+const LAST_SETTINGS_FILE: &str = "toqst-last-settings";
+// Written to the temporary directory while autosaving a test in progress
+const AUTOSAVE_FILE: &str = "toqst-autosave.json";
 const NUM_WORDS: usize = 50;
-const EXTRA_CHAR_BOUNDARY: usize = 5;
+const REFILL_THRESHOLD: usize = 10;
+const ENDURANCE_WORDS: usize = 500;
+// Endurance tests record the wpm every time this many words are finished
+const CHECKPOINT_INTERVAL: usize = 50;
 const COUNTDOWN: isize = 10;
+// Time limits that can be cycled through before the test starts. None is word mode
+const TIME_PRESETS: &[Option<isize>] = &[Some(15), Some(30), Some(60), Some(120), None];
+// How much each level up adds, and the most it can add up to
+const LEVEL_UP_SECONDS: isize = 10;
+const MAX_LEVEL_TIME_LIMIT: isize = 120;
+const LEVEL_UP_WORDS: usize = 10;
+const MAX_LEVEL_WORDS: usize = 200;
+// Endurance tests start above MAX_LEVEL_WORDS, so they level up in bigger steps of their own
+const LEVEL_UP_ENDURANCE_WORDS: usize = 100;
+const MAX_LEVEL_ENDURANCE_WORDS: usize = 1000;
+const LEVEL_UP_CHARS: usize = 50;
+const MAX_LEVEL_CHARS: usize = 1000;
+// How long the results screen ignores keys that start a new test, so a held key can not skip it
+const RESULTS_DELAY: time::Duration = time::Duration::from_millis(500);
+const COMPLETE_FLASH: time::Duration = time::Duration::from_secs(1);
+const COMPLETE_FLASH_COLOR: Color = Color::Yellow;
+const IMPERFECT_WORD_BG: Color = Color::Rgb(64, 0, 0);
+// The dark tint is as close to black as to red, so 16 color terminals get plain red instead
+const IMPERFECT_WORD_BASIC_BG: Color = Color::Red;
+// Only basic colors and modifiers so the accessible caret works on limited color terminals
+const ACCESSIBLE_WORD_BG: Color = Color::Blue;
+const GHOST_BG: Color = Color::DarkGray;
+// How long the smooth caret takes to glide to its new position
+const SMOOTH_CARET_DURATION: time::Duration = time::Duration::from_millis(80);
+const ACCESSIBLE_CURSOR_MODIFIER: Modifier = Modifier::BOLD.union(Modifier::REVERSED);
+// Size of the fake screen frames are rendered onto in bench mode
+#[cfg(feature = "bench")]
+const BENCH_AREA: Rect = Rect::new(0, 0, 80, 24);
+// Length of the rolling window the peak wpm is measured over
+const PEAK_WINDOW: time::Duration = time::Duration::from_secs(5);
+
+/// Counts every allocation so bench mode can report allocations per frame
+/// Only built with the bench feature, so normal use never pays for the counting
+#[cfg(feature = "bench")]
+struct CountingAllocator;
+
+#[cfg(feature = "bench")]
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+#[cfg(feature = "bench")]
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: AllocLayout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: AllocLayout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[cfg(feature = "bench")]
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
 
 fn main() -> Result<()> {
     color_eyre::install()?;
-    let terminal = ratatui::init();
-    let file = File::open_buffered(FILE)?;
-    let words: Vec<_> = file
-        .lines()
-        .map(|line| line.unwrap_or(String::new()).trim().to_string())
-        .filter(|word| !word.is_empty())
-        .collect();
+    let mut args = env::args().skip(1).peekable();
+    if args.next_if(|arg| arg == "stats").is_some() {
+        return print_stats(args);
+    }
+    let last_settings = load_last_settings(LAST_SETTINGS_FILE)?;
+    // Catch a broken settings file on its own, before the errors mix with the real arguments
+    let last = Config::from_args(last_settings.into_iter(), Settings::default())
+        .wrap_err_with(|| {
+            format!("Invalid last settings in {LAST_SETTINGS_FILE}, delete it to start over")
+        })?
+        .settings;
+    let config = Config::from_args(args, last)?;
+    let words = load_pool(&config)?;
+    let (rand_words, refill_pool) = draw_words(&words, &config);
+    // The ghost is only extra, a history that can not be read runs the test without it
+    let mut ghost_notice = None;
+    let personal_best = if config.ghost {
+        match load_history(&config.history_file) {
+            Ok(history) => history.iter().map(|result| result.wpm).reduce(f64::max),
+            Err(err) => {
+                ghost_notice = Some(format!("Could not load the ghost: {err}"));
+                None
+            }
+        }
+    } else {
+        None
+    };
 
-    // TODO: Should there be a restart option instead of only generating on startup
-    let mut rng = rand::thread_rng();
-    let rand_words = words.iter().choose_multiple(&mut rng, NUM_WORDS);
+    let mut app = App::new(rand_words, refill_pool, personal_best, config);
+    if ghost_notice.is_some() {
+        app.notice = ghost_notice;
+    }
+    #[cfg(feature = "bench")]
+    if let Some(frames) = app.config.bench {
+        run_bench(app, frames);
+        return Ok(());
+    }
 
-    let app_result = App::new(rand_words).run(terminal);
+    let terminal = ratatui::init();
+    // Not every terminal reports focus changes, those that do not simply never pause
+    if app.config.pause_on_blur {
+        execute!(io::stdout(), EnableFocusChange)?;
+    }
+    let app_result = app.run(terminal);
+    if app.config.pause_on_blur {
+        execute!(io::stdout(), DisableFocusChange)?;
+    }
     ratatui::restore();
+    save_last_settings(LAST_SETTINGS_FILE, &app.config)?;
     // TODO: game loop so go to end game screen and give option to repeat
     println!("Game is done");
     app_result
 }
 
+/// The arguments saved by the last run, one per line
+/// A missing file means there are no last settings yet
+fn load_last_settings(path: impl AsRef<Path>) -> Result<Vec<String>> {
+    let path = path.as_ref();
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(contents.lines().map(String::from).collect()),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => {
+            Err(err).wrap_err_with(|| format!("Failed to read last settings {}", path.display()))
+        }
+    }
+}
+
+/// Remember the mode, time limit and theme for the next launch
+fn save_last_settings(path: impl AsRef<Path>, config: &Config) -> Result<()> {
+    let path = path.as_ref();
+    let contents: String = config
+        .settings
+        .args()
+        .into_iter()
+        .map(|arg| arg + "\n")
+        .collect();
+    fs::write(path, contents)
+        .wrap_err_with(|| format!("Failed to write last settings {}", path.display()))
+}
+
+/// Read the word files into the pool of words tests are drawn from
+/// Text given on the command line is used as is instead of reading any files
+fn load_pool(config: &Config) -> Result<Vec<String>> {
+    if let Some(text) = &config.text {
+        let words: Vec<String> = text.split_whitespace().map(String::from).collect();
+        if words.is_empty() {
+            bail!("--text needs at least one word to type");
+        }
+        return Ok(words);
+    }
+    let words = load_word_files(&config.word_files(), config.allow_tabs)?;
+    let words = filter_words(words, &config.word_filter)?;
+    Ok(if config.dedup {
+        dedup_words(words, config.dedup_ignore_case)
+    } else {
+        words
+    })
+}
+
+/// Pick the words for a new test from the pool, along with the pool to refill the test from
+/// A pool smaller than the word count gives a shorter test, unless words are allowed to repeat
+fn draw_words<'a>(pool: &'a [String], config: &Config) -> (Vec<&'a String>, Vec<String>) {
+    let mut rng = rand::thread_rng();
+    // Text from the command line is typed in the order it was given
+    let rand_words = if config.text.is_some() {
+        pool.iter().collect()
+    } else if config.repeat_words {
+        // Draw from the whole pool again as many times as it takes to reach the word count
+        let mut rand_words = Vec::with_capacity(config.word_count);
+        while rand_words.len() < config.word_count {
+            let missing = config.word_count - rand_words.len();
+            rand_words.extend(pool.iter().choose_multiple(&mut rng, missing));
+        }
+        rand_words
+    } else {
+        pool.iter().choose_multiple(&mut rng, config.word_count)
+    };
+    // In time mode the test should only end on the timer, so keep the pool around to draw from
+    let refill_pool = if config.completion == CompletionMode::TimeOnly
+        || config.correct_target.is_some()
+        || config.char_limit.is_some()
+    {
+        pool.to_vec()
+    } else {
+        Vec::new()
+    };
+    (rand_words, refill_pool)
+}
+
+/// Render frames onto an in memory buffer while typing the words perfectly, then print the
+/// average time and allocations it took to build a frame
+/// Used to measure changes to the render path without a real terminal
+#[cfg(feature = "bench")]
+fn run_bench(mut app: App, frames: usize) {
+    let mut buf = Buffer::empty(BENCH_AREA);
+    let mut elapsed = time::Duration::ZERO;
+    let mut allocations = 0;
+    for _ in 0..frames {
+        let cursor_word = app.cursor.get_cursor_word();
+        let ch = cursor_word
+            .word
+            .chars
+            .get(cursor_word.cursor_idx)
+            .map_or(' ', StyledChar::get_char_data);
+        app.handle_char_read(ch);
+        buf.reset();
+        let allocations_before = ALLOCATIONS.load(Ordering::Relaxed);
+        let frame_start = time::Instant::now();
+        (&app).render(BENCH_AREA, &mut buf);
+        elapsed += frame_start.elapsed();
+        allocations += ALLOCATIONS.load(Ordering::Relaxed) - allocations_before;
+    }
+    let frames = frames.max(1);
+    println!(
+        "{frames} frames: {:.1}us and {:.1} allocations per frame on average",
+        elapsed.as_secs_f64() * 1e6 / frames as f64,
+        allocations as f64 / frames as f64
+    );
+}
+
+/// Print a summary of the test history to stdout without starting the terminal UI
+fn print_stats(mut args: impl Iterator<Item = String>) -> Result<()> {
+    let mut json = false;
+    let mut history_file = PathBuf::from(HISTORY_FILE);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--history" => history_file = next_value(&mut args, &arg)?,
+            _ => bail!("Unknown argument: {arg}"),
+        }
+    }
+    print!(
+        "{}",
+        stats_report(&history_file, json, Local::now().date_naive())?
+    );
+    Ok(())
+}
+
+/// The summary of the history file as of the given day, as text or as a JSON object
+fn stats_report(history_file: &Path, json: bool, today: NaiveDate) -> Result<String> {
+    let summary = summarize(&load_history(history_file)?, today);
+    Ok(if json {
+        format!(
+            "{{\"tests\":{},\"average_wpm\":{:.2},\"best_wpm\":{:.2},\"streak\":{}}}\n",
+            summary.tests, summary.average_wpm, summary.best_wpm, summary.streak
+        )
+    } else {
+        format!(
+            "Tests taken: {}\nAverage WPM: {:.1}\nBest WPM:    {:.1}\nStreak:      {} day(s)\n",
+            summary.tests, summary.average_wpm, summary.best_wpm, summary.streak
+        )
+    })
+}
+
+/// User configurable options for the Speed Typing Test
+///
+/// Options are parsed from the command line arguments
+#[derive(Debug, Clone)]
+struct Config {
+    complete_signal: bool, // Ring the bell and flash the border when the test is done
+    completion: CompletionMode, // Whether running out of words, time, or either ends the test
+    speed_gradient: bool,  // Color correct characters by how quickly they were typed
+    word_heat: bool,       // Tint finished words that were typed with mistakes
+    time_limit: Option<isize>, // Seconds the user has to type. None to only end on words
+    max_mistakes: Option<usize>, // Fail the test once more mistakes than this are made
+    accessible: bool,      // Large high contrast caret for low vision users
+    history_file: PathBuf, // Where finished tests are recorded
+    ghost: bool,           // Race against a marker moving at personal best pace
+    word_filter: WordFilter, // Kinds of words to remove from the word pool
+    smooth_caret: bool,    // Animate the caret between cells instead of jumping
+    allow_tabs: bool,      // Keep tabs in the word file instead of stripping them
+    save_history: bool,    // Record the test in the history file once it is finished
+    word_files: Vec<PathBuf>, // Word lists merged into the pool. Empty for the default list
+    dedup: bool,           // Remove repeated words from the pool
+    dedup_ignore_case: bool, // Words that only differ by case count as repeats
+    word_count: usize,     // Number of words drawn for the test
+    endurance: bool,       // Long word test with wpm checkpoints along the way
+    allow_word_skip: bool, // A space on an untyped word skips it instead of being ignored
+    reverse: bool,         // Type every word backward, from its last character to its first
+    scroll_anchor: f64,    // Fraction of the text height the active line is kept at
+    bench: Option<usize>,  // Render this many frames without a terminal and print the timing
+    count_spaces: bool,    // Count spaces between words as characters. Off by default
+    export_file: Option<PathBuf>, // Write the finished test to this file as JSON
+    private_export: bool,  // Leave the typed words out of the export
+    autosave_interval: Option<time::Duration>, // How often to snapshot a test in progress
+    autosave_file: PathBuf, // Where the progress snapshot is written
+    flashcard: bool,       // Only show the word being typed
+    mistake_grace: Option<time::Duration>, // How long a new mistake stays uncolored
+    results_delay: time::Duration, // How long the results are shown before a new test can start
+    preview: bool,         // Show the words on their own screen before starting
+    show_kps: bool,        // Show keystrokes per second while typing and in the results
+    show_error_count: bool, // Show the number of mistakes not yet fixed while typing
+    show_odometer: bool,   // Show the keys and words typed since launch while typing
+    pause_on_blur: bool,   // Pause the test while the terminal is not focused
+    color_spaces: bool,    // Color the spaces between words like the characters of a word
+    theme: Theme,          // Style of each typed state
+    color_support: ColorSupport, // Colors the terminal can draw, the theme is fitted to them
+    correct_target: Option<usize>, // Finish once this many words are typed without a mistake
+    char_limit: Option<usize>, // Finish once this many characters are typed correctly
+    wpm_in_title: bool,    // Show the wpm in the title of the status block
+    text: Option<String>,  // Type exactly this text instead of words from the word files
+    freeze_on_error: bool, // Only move the caret on the right key, counting the wrong ones
+    timing_export_file: Option<PathBuf>, // Write the timing of every keystroke here as JSON
+    relaxed_spacing: bool, // Insert a forgotten space when the next word is started
+    visible_words: Option<usize>, // Words shown ahead of the one being typed. None shows all
+    sound: bool,           // Click on every keystroke, only with the sound feature
+    target_wpm: Option<f64>, // Wpm to pace against while typing. None hides the pacer
+    delete_key: DeleteKey, // What the Delete key does
+    repeat_words: bool,    // Repeat words when the pool is smaller than the word count
+    settings: Settings,    // What the user chose last time and this time, saved for next time
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            complete_signal: false,
+            completion: CompletionMode::Either,
+            speed_gradient: false,
+            word_heat: false,
+            time_limit: Some(COUNTDOWN),
+            max_mistakes: None,
+            accessible: false,
+            history_file: PathBuf::from(HISTORY_FILE),
+            ghost: false,
+            word_filter: WordFilter::default(),
+            smooth_caret: false,
+            allow_tabs: false,
+            save_history: true,
+            word_files: Vec::new(),
+            dedup: false,
+            dedup_ignore_case: false,
+            word_count: NUM_WORDS,
+            endurance: false,
+            allow_word_skip: false,
+            reverse: false,
+            scroll_anchor: 0.5,
+            bench: None,
+            count_spaces: false,
+            export_file: None,
+            private_export: false,
+            autosave_interval: None,
+            autosave_file: env::temp_dir().join(AUTOSAVE_FILE),
+            flashcard: false,
+            mistake_grace: None,
+            results_delay: RESULTS_DELAY,
+            preview: false,
+            show_kps: false,
+            show_error_count: false,
+            show_odometer: false,
+            pause_on_blur: false,
+            color_spaces: false,
+            theme: Theme::default(),
+            color_support: ColorSupport::detect(),
+            correct_target: None,
+            char_limit: None,
+            wpm_in_title: false,
+            text: None,
+            freeze_on_error: false,
+            timing_export_file: None,
+            relaxed_spacing: false,
+            visible_words: None,
+            sound: false,
+            target_wpm: None,
+            delete_key: DeleteKey::Backspace,
+            repeat_words: false,
+            settings: Settings::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Parse the command line flags on top of the settings remembered from the last launch
+    /// A flag always wins over its remembered setting
+    fn from_args(mut args: impl Iterator<Item = String>, last: Settings) -> Result<Self> {
+        let mut config = Config::default();
+        // Only what the user passed this time, the defaults are filled in after parsing
+        let mut chosen = Settings::default();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--complete-signal" => config.complete_signal = true,
+                "--time-mode" => chosen.completion = Some(CompletionMode::TimeOnly),
+                "--finish" => chosen.completion = Some(next_value(&mut args, &arg)?),
+                "--delete-key" => config.delete_key = next_value(&mut args, &arg)?,
+                "--speed-gradient" => config.speed_gradient = true,
+                "--word-heat" => config.word_heat = true,
+                "--accessible" => config.accessible = true,
+                "--history" => config.history_file = next_value(&mut args, &arg)?,
+                "--ghost" => config.ghost = true,
+                "--no-apostrophe" => config.word_filter.no_apostrophe = true,
+                "--no-hyphen" => config.word_filter.no_hyphen = true,
+                "--alpha-only" => config.word_filter.alpha_only = true,
+                "--smooth-caret" => config.smooth_caret = true,
+                "--allow-tabs" => config.allow_tabs = true,
+                "--no-save" => config.save_history = false,
+                "--words" => config.word_files.push(next_value(&mut args, &arg)?),
+                "--text" => config.text = Some(next_value(&mut args, &arg)?),
+                // A drill is typed just like text given with --text
+                "--drill" => {
+                    let name: String = next_value(&mut args, &arg)?;
+                    let Some(text) = drill_text(&name) else {
+                        let names: Vec<&str> = DRILLS.iter().map(|(name, _)| *name).collect();
+                        bail!("Unknown drill: {name}, choose from {}", names.join(", "));
+                    };
+                    config.text = Some(String::from(text));
+                }
+                "--repeat-words" => config.repeat_words = true,
+                "--freeze-on-error" => config.freeze_on_error = true,
+                "--relaxed-spacing" => config.relaxed_spacing = true,
+                "--sound" => {
+                    if !sound::AVAILABLE {
+                        bail!("--sound needs toqst-typer to be built with the sound feature");
+                    }
+                    config.sound = true;
+                }
+                "--dedup" => config.dedup = true,
+                "--allow-word-skip" => config.allow_word_skip = true,
+                "--reverse" => config.reverse = true,
+                "--count-spaces" => config.count_spaces = true,
+                "--export" => config.export_file = Some(next_value(&mut args, &arg)?),
+                "--private-export" => config.private_export = true,
+                "--export-timing" => config.timing_export_file = Some(next_value(&mut args, &arg)?),
+                "--autosave" => {
+                    let seconds: u64 = next_value(&mut args, &arg)?;
+                    if seconds == 0 {
+                        bail!("--autosave must be a positive number of seconds");
+                    }
+                    config.autosave_interval = Some(time::Duration::from_secs(seconds));
+                }
+                "--autosave-file" => config.autosave_file = next_value(&mut args, &arg)?,
+                "--flashcard" => config.flashcard = true,
+                "--visible-words" => config.visible_words = Some(next_value(&mut args, &arg)?),
+                "--preview" => config.preview = true,
+                "--kps" => config.show_kps = true,
+                "--error-count" => config.show_error_count = true,
+                "--odometer" => config.show_odometer = true,
+                "--pause-on-blur" => config.pause_on_blur = true,
+                "--color-spaces" => config.color_spaces = true,
+                "--wpm-in-title" => config.wpm_in_title = true,
+                "--caret-fg" => chosen.caret_fg = Some(next_value(&mut args, &arg)?),
+                "--colors" => config.color_support = next_value(&mut args, &arg)?,
+                "--caret-bg" => chosen.caret_bg = Some(next_value(&mut args, &arg)?),
+                "--target-wpm" => {
+                    let target: f64 = next_value(&mut args, &arg)?;
+                    if !target.is_finite() || target <= 0.0 {
+                        bail!("--target-wpm must be a positive number");
+                    }
+                    config.target_wpm = Some(target);
+                }
+                "--correct-target" => {
+                    let target: usize = next_value(&mut args, &arg)?;
+                    if target == 0 {
+                        bail!("--correct-target must be at least one word");
+                    }
+                    config.correct_target = Some(target);
+                }
+                "--char-limit" => {
+                    let limit: usize = next_value(&mut args, &arg)?;
+                    if limit == 0 {
+                        bail!("--char-limit must be at least one character");
+                    }
+                    config.char_limit = Some(limit);
+                }
+                "--mistype-color" => chosen.mistype_color = Some(next_value(&mut args, &arg)?),
+                "--extra-color" => chosen.extra_color = Some(next_value(&mut args, &arg)?),
+                "--grace" => {
+                    let millis = next_value(&mut args, &arg)?;
+                    config.mistake_grace = Some(time::Duration::from_millis(millis));
+                }
+                "--results-delay" => {
+                    let millis = next_value(&mut args, &arg)?;
+                    config.results_delay = time::Duration::from_millis(millis);
+                }
+                "--scroll-anchor" => {
+                    let anchor: f64 = next_value(&mut args, &arg)?;
+                    if !(0.0..=1.0).contains(&anchor) {
+                        bail!("--scroll-anchor must be between 0 and 1");
+                    }
+                    config.scroll_anchor = anchor;
+                }
+                "--endurance" => {
+                    config.endurance = true;
+                    config.word_count = ENDURANCE_WORDS;
+                }
+                "--dedup-ignore-case" => {
+                    config.dedup = true;
+                    config.dedup_ignore_case = true;
+                }
+                "--time" => {
+                    let seconds: isize = next_value(&mut args, &arg)?;
+                    if seconds <= 0 {
+                        bail!("--time must be a positive number of seconds");
+                    }
+                    chosen.time_limit = Some(Some(seconds));
+                }
+                "--word-mode" => chosen.time_limit = Some(None),
+                "--max-mistakes" => config.max_mistakes = Some(next_value(&mut args, &arg)?),
+                // Not listed anywhere, only meant for working on the render path
+                #[cfg(feature = "bench")]
+                "--bench" => {
+                    config.bench = Some(next_value(&mut args, &arg)?);
+                    config.save_history = false;
+                }
+                _ => bail!("Unknown argument: {arg}"),
+            }
+        }
+        config.apply_settings(last.under(chosen), &chosen);
+        // A bench never finishes, so it is the only test allowed to finish on time without a
+        // time limit
+        if config.completion == CompletionMode::TimeOnly
+            && config.time_limit.is_none()
+            && config.bench.is_none()
+        {
+            bail!("Finishing on time only needs a time limit, it can not be used with --word-mode");
+        }
+        if config.private_export && config.export_file.is_none() {
+            bail!("--private-export needs a file to export to with --export");
+        }
+        // A single word gives the smooth caret nowhere to glide, use the regular caret instead
+        if config.flashcard {
+            config.smooth_caret = false;
+        }
+        Ok(config)
+    }
+
+    /// Make the test a step harder along whatever ends it, and describe the change
+    /// A character limit gets more characters, a correct word target and a test without a
+    /// timer get more words, and a timed test gets more time. Each is capped, and fixed text
+    /// can not be made any longer. An endurance test has its own larger steps and cap
+    /// None when the test is already as hard as it gets
+    fn level_up(&mut self) -> Option<String> {
+        if self.text.is_some() {
+            return None;
+        }
+        let step_up = |value: usize, step: usize, max: usize| {
+            (value < max).then(|| cmp::min(value + step, max))
+        };
+        if let Some(limit) = self.char_limit {
+            let limit = step_up(limit, LEVEL_UP_CHARS, MAX_LEVEL_CHARS)?;
+            self.char_limit = Some(limit);
+            return Some(format!("{limit} characters"));
+        }
+        if let Some(target) = self.correct_target {
+            let target = step_up(target, LEVEL_UP_WORDS, MAX_LEVEL_WORDS)?;
+            self.correct_target = Some(target);
+            return Some(format!("{target} correct words"));
+        }
+        match self.time_limit {
+            Some(seconds) if self.completion != CompletionMode::WordsOnly => {
+                if seconds >= MAX_LEVEL_TIME_LIMIT {
+                    return None;
+                }
+                let seconds = cmp::min(seconds + LEVEL_UP_SECONDS, MAX_LEVEL_TIME_LIMIT);
+                self.time_limit = Some(seconds);
+                Some(format!("{seconds}s"))
+            }
+            _ => {
+                let (step, max) = if self.endurance {
+                    (LEVEL_UP_ENDURANCE_WORDS, MAX_LEVEL_ENDURANCE_WORDS)
+                } else {
+                    (LEVEL_UP_WORDS, MAX_LEVEL_WORDS)
+                };
+                self.word_count = step_up(self.word_count, step, max)?;
+                Some(format!("{} words", self.word_count))
+            }
+        }
+    }
+
+    /// Fill in the time limit, completion mode and theme from the remembered settings
+    /// A test that ends on a word or character target, or an endurance test, has no timer
+    /// unless one is asked for on the command line. The remembered time limit is not used for
+    /// it, but it is still kept for the next launch
+    fn apply_settings(&mut self, settings: Settings, chosen: &Settings) {
+        let untimed = self.endurance || self.correct_target.is_some() || self.char_limit.is_some();
+        let (time_limit, completion) = if untimed && !chosen.sets_mode() {
+            (None, None)
+        } else {
+            (settings.time_limit, settings.completion)
+        };
+        self.time_limit = match time_limit {
+            Some(time_limit) => time_limit,
+            None if untimed => None,
+            None => Some(COUNTDOWN),
+        };
+        self.completion = completion.unwrap_or(CompletionMode::Either);
+        if let Some(color) = settings.mistype_color {
+            self.theme.mistype = self.theme.mistype.fg(color);
+        }
+        if let Some(color) = settings.extra_color {
+            self.theme.mistype_extra = self.theme.mistype_extra.fg(color);
+        }
+        if let Some(color) = settings.caret_fg {
+            self.theme.caret = self.theme.caret.fg(color);
+        }
+        if let Some(color) = settings.caret_bg {
+            self.theme.caret = self.theme.caret.bg(color);
+        }
+        // Keep refilling the words so every frame of a bench is rendered mid test
+        if self.bench.is_some() {
+            self.completion = CompletionMode::TimeOnly;
+            self.time_limit = None;
+        }
+        self.settings = settings;
+    }
+
+    /// The word lists to draw words from
+    fn word_files(&self) -> Vec<PathBuf> {
+        if self.word_files.is_empty() {
+            vec![PathBuf::from(FILE)]
+        } else {
+            self.word_files.clone()
+        }
+    }
+}
+
+/// The settings remembered between launches
+/// Each one is only set when the user chose it, so defaults and values other flags imply are
+/// never saved
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct Settings {
+    time_limit: Option<Option<isize>>, // From --time, or None inside from --word-mode
+    completion: Option<CompletionMode>, // From --finish or --time-mode
+    mistype_color: Option<Color>,
+    extra_color: Option<Color>,
+    caret_fg: Option<Color>,
+    caret_bg: Option<Color>,
+}
+
+impl Settings {
+    /// Whether the time limit or completion mode is set
+    fn sets_mode(&self) -> bool {
+        self.time_limit.is_some() || self.completion.is_some()
+    }
+
+    /// These settings with every setting chosen in `over` taking the place of its own
+    /// The time limit and completion mode only make sense together, so choosing either one
+    /// replaces both
+    fn under(self, over: Settings) -> Settings {
+        let (time_limit, completion) = if over.sets_mode() {
+            (over.time_limit, over.completion)
+        } else {
+            (self.time_limit, self.completion)
+        };
+        Settings {
+            time_limit,
+            completion,
+            mistype_color: over.mistype_color.or(self.mistype_color),
+            extra_color: over.extra_color.or(self.extra_color),
+            caret_fg: over.caret_fg.or(self.caret_fg),
+            caret_bg: over.caret_bg.or(self.caret_bg),
+        }
+    }
+
+    /// The arguments that recreate these settings
+    fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        match self.time_limit {
+            Some(Some(seconds)) => args.extend([String::from("--time"), seconds.to_string()]),
+            Some(None) => args.push(String::from("--word-mode")),
+            None => {}
+        }
+        if let Some(completion) = self.completion {
+            args.extend([String::from("--finish"), completion.label().to_string()]);
+        }
+        let colors = [
+            ("--mistype-color", self.mistype_color),
+            ("--extra-color", self.extra_color),
+            ("--caret-fg", self.caret_fg),
+            ("--caret-bg", self.caret_bg),
+        ];
+        for (flag, color) in colors {
+            if let Some(color) = color {
+                args.extend([flag.to_string(), color.to_string()]);
+            }
+        }
+        args
+    }
+}
+
+/// What ends a test, besides going over the mistake budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CompletionMode {
+    WordsOnly, // Finish every word, the time limit is only shown
+    TimeOnly,  // Type until the time runs out, more words are added as needed
+    Either,    // Whichever of the words or the time runs out first
+}
+
+impl CompletionMode {
+    fn label(self) -> &'static str {
+        match self {
+            CompletionMode::WordsOnly => "words",
+            CompletionMode::TimeOnly => "time",
+            CompletionMode::Either => "either",
+        }
+    }
+}
+
+impl FromStr for CompletionMode {
+    type Err = color_eyre::Report;
+
+    fn from_str(mode: &str) -> Result<Self> {
+        match mode {
+            "words" => Ok(CompletionMode::WordsOnly),
+            "time" => Ok(CompletionMode::TimeOnly),
+            "either" => Ok(CompletionMode::Either),
+            _ => bail!("Unknown completion mode: {mode}"),
+        }
+    }
+}
+
+/// Typing done across every test since launch, kept in memory only
+#[derive(Debug, Default, Clone, Copy)]
+struct SessionCount {
+    keys: usize,  // Characters and spaces typed
+    words: usize, // Words finished
+}
+
+/// What the Delete key does, Backspace always erases the last character
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DeleteKey {
+    Backspace, // Erase the last character like Backspace
+    Word,      // Erase everything typed in the word, or the previous word at the start of one
+    Nothing,   // Ignore the key
+}
+
+impl DeleteKey {
+    fn label(self) -> &'static str {
+        match self {
+            DeleteKey::Backspace => "erase the last character",
+            DeleteKey::Word => "erase the word",
+            DeleteKey::Nothing => "does nothing",
+        }
+    }
+}
+
+impl FromStr for DeleteKey {
+    type Err = color_eyre::Report;
+
+    fn from_str(policy: &str) -> Result<Self> {
+        match policy {
+            "backspace" => Ok(DeleteKey::Backspace),
+            "word" => Ok(DeleteKey::Word),
+            "none" => Ok(DeleteKey::Nothing),
+            _ => bail!("Unknown delete key behavior: {policy}"),
+        }
+    }
+}
+
+/// Parse the value that follows a command line flag
+fn next_value<T: FromStr>(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<T> {
+    let value = args
+        .next()
+        .ok_or_else(|| eyre!("Missing value for {flag}"))?;
+    value
+        .parse()
+        .map_err(|_| eyre!("Invalid value for {flag}: {value}"))
+}
+
+/// Whether a character key is pressed as a shortcut rather than typed
+/// Shift only changes the character, and Ctrl and Alt together are AltGr on Windows, which
+/// types characters such as @ on many layouts
+fn is_shortcut(modifiers: KeyModifiers) -> bool {
+    let alt_gr = KeyModifiers::CONTROL | KeyModifiers::ALT;
+    if modifiers.contains(alt_gr) {
+        return modifiers.contains(KeyModifiers::SUPER);
+    }
+    modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SUPER)
+}
+
+/// A count with its thousands separated, followed by the noun made plural when needed
+fn count_label(count: usize, noun: &str) -> String {
+    let plural = if count == 1 { "" } else { "s" };
+    format!("{} {noun}{plural}", fmt_count(count))
+}
+
+/// Human readable form of a time limit
+fn time_limit_label(time_limit: Option<isize>) -> String {
+    match time_limit {
+        Some(seconds) => format!("{seconds}s"),
+        None => String::from("words"),
+    }
+}
+
 #[derive(Debug)]
 struct CursorWord {
     word: StyledWord,
     cursor_idx: usize,
+    separator: StyledChar, // The space after the word, typed once the word is finished
+}
+
+impl CursorWord {
+    fn new(word: &str) -> Self {
+        Self {
+            word: StyledWord::from_string(word),
+            cursor_idx: 0,
+            separator: StyledChar::new(' '),
+        }
+    }
+
+    /// Index of the character the cursor is on
+    /// In reverse mode the original characters are typed from the last one to the first, while
+    /// characters typed past the end of the word are still appended after it
+    fn char_idx(&self, reverse: bool) -> usize {
+        if reverse && self.cursor_idx < self.word.og_len {
+            self.word.og_len - 1 - self.cursor_idx
+        } else {
+            self.cursor_idx
+        }
+    }
 }
 
 #[derive(Debug)]
 struct UserCursor {
-    word_idx: usize,        // Position of the cursor in the word list
-    words: Vec<CursorWord>, // Vector of words to type
+    word_idx: usize,                       // Position of the cursor in the word list
+    words: Vec<CursorWord>,                // Vector of words to type
+    refill_pool: Vec<String>, // Words to append from when nearing the end. Empty to never refill
+    speed_gradient: bool,     // Color correct characters by the time since the last keystroke
+    last_keystroke: Option<time::Instant>, // When the user last typed a character or space
+    word_heat: bool,          // Tint words behind the cursor that have mistakes
+    mistakes_made: usize,     // Every mistype and extra character, even if later corrected
+    accessible: bool,         // Highlight the whole active word and reverse the caret cell
+    keystrokes: usize,        // Characters typed, not deletes. Spaces only when counting them
+    smooth_caret: bool,       // The caret is drawn over the text by the App instead
+    last_mistake: Option<Mistake>, // The most recent mistake the user made
+    allow_word_skip: bool,    // Let a space advance past a word with nothing typed
+    reverse: bool,            // Type each word from its last character to its first
+    count_spaces: bool,       // Spaces count as typed characters for wpm and accuracy
+    theme: Theme,             // Style of each typed state
+    correct_words: usize,     // Finished words that were typed without a mistake
+    correct_typed_at: Vec<time::Instant>, // When each character still correct was typed, in order
+    freeze_on_error: bool,    // A wrong key keeps the caret in place until the right key
+    wrong_attempts: usize,    // Wrong keys pressed while the caret was frozen
+    keystroke_log: Vec<Keystroke>, // Every character and space typed, in order
+    words_typed: usize,       // Words finished, never going down when a word is reopened
+    color_spaces: bool,       // Color the space after each finished word by how it was typed
+    relaxed_spacing: bool,    // Typing the start of the next word at the end of a word advances
+    color_support: ColorSupport, // Colors the terminal can draw, for colors outside the theme
+}
+
+/// A key the user typed while taking the test
+#[derive(Debug, Clone, Copy)]
+struct Keystroke {
+    expected: char,
+    typed: char,
+    at: time::Instant,
+}
+
+/// A single wrong keystroke
+#[derive(Debug, Clone, Copy)]
+struct Mistake {
+    word_idx: usize,
+    char_idx: usize,
+    typed: char,
+    expected: Option<char>, // None when the character was typed past the end of the word
 }
 
 /// Game Logic for the Speed Typing Test
@@ -76,68 +924,342 @@ impl UserCursor {
     fn is_game_done(&self) -> bool {
         self.word_idx == self.words.len()
     }
+
+    /// The words from the one being typed to the end, as they were before being typed
+    fn untyped_tail(&self) -> Vec<String> {
+        self.words[cmp::min(self.word_idx, self.words.len())..]
+            .iter()
+            .map(|CursorWord { word, .. }| {
+                word.chars[..word.og_len]
+                    .iter()
+                    .map(StyledChar::get_char_data)
+                    .collect()
+            })
+            .collect()
+    }
+    /// A space only moves to the next word once something has been typed in the current one
+    /// so a reflexive double space, or a space before starting, does not skip a word
+    fn can_advance(&self) -> bool {
+        self.allow_word_skip || self.get_cursor_word().cursor_idx > 0
+    }
+
     fn handle_space_press(&mut self) {
+        if !self.can_advance() {
+            return;
+        }
+        self.last_keystroke = Some(time::Instant::now());
+        self.log_keystroke(' ');
+        if self.count_spaces {
+            self.keystrokes += 1;
+        }
+        // The space is only in its place right at the end of the word. Before that it cuts the
+        // word short, and after it the characters typed past the end took its place. A key
+        // pressed in its place while the caret was frozen also leaves it a mistake
+        let cursor_word = self.get_cursor_word();
+        let in_place = cursor_word.cursor_idx == cursor_word.word.og_len;
+        let separator =
+            if in_place && cursor_word.separator.get_typed_state() != TypedState::Mistype {
+                TypedState::Correct
+            } else {
+                TypedState::Mistype
+            };
+        self.advance_word(separator);
+    }
+
+    /// Finish the current word and move onto the next one, with the state of the space after it
+    fn advance_word(&mut self, separator: TypedState) {
+        self.words[self.word_idx]
+            .separator
+            .switch_typed_state(separator);
+        if self.get_cursor_word().word.correctness() == Correctness::Perfect {
+            self.correct_words += 1;
+        }
         self.word_idx += 1;
+        self.words_typed += 1;
+        if !self.refill_pool.is_empty() && self.words.len() - self.word_idx <= REFILL_THRESHOLD {
+            self.refill();
+        }
     }
 
-    fn get_cursor_word<'a>(&'a self) -> &'a CursorWord {
+    /// Append another batch of random words from the refill pool onto the end of the word list
+    fn refill(&mut self) {
+        let mut rng = rand::thread_rng();
+        let new_words = self.refill_pool.iter().choose_multiple(&mut rng, NUM_WORDS);
+        self.words
+            .extend(new_words.into_iter().map(|str| CursorWord::new(str)));
+    }
+
+    fn get_cursor_word(&self) -> &CursorWord {
         &self.words[self.word_idx]
     }
 
+    fn cursor_modifier(&self) -> Modifier {
+        if self.accessible {
+            ACCESSIBLE_CURSOR_MODIFIER
+        } else {
+            Modifier::BOLD | Modifier::UNDERLINED
+        }
+    }
+
+    /// The theme's caret colors along with the caret modifier
+    fn caret_style(&self) -> Style {
+        self.theme.caret.add_modifier(self.cursor_modifier())
+    }
+
     /// Style a word that is contained within the cursor word list
     /// It is assumed that each word is owened by the cursor and thus will live as long as the
     /// cursor
     /// It is assumed that each word is separated by a space word
-    fn style_word<'a>(
-        &'a self,
-        idx: usize,
-        CursorWord { word, cursor_idx }: &'a CursorWord,
-    ) -> Vec<Span<'a>> {
+    fn style_word<'a>(&'a self, idx: usize, styled_word: &'a CursorWord) -> Vec<Span<'a>> {
+        let CursorWord {
+            word, cursor_idx, ..
+        } = styled_word;
+        let caret_idx = styled_word.char_idx(self.reverse);
         let cursor_word = self.get_cursor_word();
 
         let word_length = cursor_word.word.chars.len();
 
+        // The smooth caret is drawn on top of the text after it is rendered
+        let show_caret = !self.smooth_caret;
         // Each word is on an even index
-        let cursor_in_word = idx == 2 * self.word_idx && *cursor_idx < word_length;
+        let cursor_in_word = show_caret && idx == 2 * self.word_idx && *cursor_idx < word_length;
         // Each space is on an odd index
-        let cursor_on_space = idx == 2 * self.word_idx + 1 && cursor_word.cursor_idx == word_length;
+        let cursor_on_space =
+            show_caret && idx == 2 * self.word_idx + 1 && cursor_word.cursor_idx == word_length;
 
         assert!(
             cursor_word.cursor_idx <= word_length,
             "A cursor should be inside of the designated word or on the space after the word"
         );
 
-        let cursor_modifier = Modifier::BOLD | Modifier::UNDERLINED;
+        let caret_style = self.caret_style();
+        // Reversing the caret cell on top of the word background makes a block that stands out
+        // even without color support
+        if self.accessible && idx == 2 * self.word_idx {
+            let spans = if cursor_in_word {
+                word.get_styled_with_caret(caret_idx, caret_style, &self.theme)
+            } else {
+                word.get_styled_word(&self.theme)
+            };
+            return spans
+                .into_iter()
+                .map(|span| span.bg(ACCESSIBLE_WORD_BG))
+                .collect();
+        }
         if cursor_in_word {
-            return word.get_styled_with_modifier(*cursor_idx, cursor_modifier);
+            return word.get_styled_with_caret(caret_idx, caret_style, &self.theme);
         } else if cursor_on_space {
-            return vec![Span::styled(
-                " ",
-                Style::default().add_modifier(cursor_modifier),
-            )];
+            return vec![Span::styled(" ", caret_style)];
+        }
+
+        if let Some(style) = self.separator_style(idx) {
+            return vec![Span::styled(" ", style)];
+        }
+
+        // Words behind the cursor have been finished and can be judged
+        let finished_word = idx.is_multiple_of(2) && idx / 2 < self.word_idx;
+        if self.word_heat && finished_word && word.correctness() == Correctness::Imperfect {
+            return word
+                .get_styled_word(&self.theme)
+                .into_iter()
+                .map(|span| span.bg(self.imperfect_word_bg()))
+                .collect();
+        }
+        word.get_styled_word(&self.theme)
+    }
+
+    /// The tint of finished words with mistakes, in colors the terminal can draw
+    fn imperfect_word_bg(&self) -> Color {
+        match self.color_support {
+            ColorSupport::Basic => IMPERFECT_WORD_BASIC_BG,
+            support => downgrade_color(IMPERFECT_WORD_BG, support),
+        }
+    }
+
+    /// The style of a typed space when spaces are colored, None for a word or an untyped space
+    /// A space has no glyph to color, so it is underlined in the color of its state instead
+    fn separator_style(&self, idx: usize) -> Option<Style> {
+        if !self.color_spaces || idx.is_multiple_of(2) {
+            return None;
+        }
+        let separator = &self.words.get(idx / 2)?.separator;
+        (separator.get_typed_state() != TypedState::Untyped).then(|| {
+            separator
+                .style(&self.theme)
+                .add_modifier(Modifier::UNDERLINED)
+        })
+    }
+
+    /// The character the user is expected to type next, a space at the end of the word
+    fn expected_char(&self) -> char {
+        let cursor_word = self.get_cursor_word();
+        cursor_word
+            .word
+            .chars
+            .get(cursor_word.char_idx(self.reverse))
+            .filter(|_| cursor_word.cursor_idx < cursor_word.word.og_len)
+            .map_or(' ', StyledChar::get_char_data)
+    }
+
+    /// Every character and space key pressed, whether or not spaces are counted for scoring
+    fn key_presses(&self) -> usize {
+        self.keystroke_log.len()
+    }
+
+    fn log_keystroke(&mut self, typed: char) {
+        self.keystroke_log.push(Keystroke {
+            expected: self.expected_char(),
+            typed,
+            at: time::Instant::now(),
+        });
+    }
+
+    /// Every keystroke along with the time since the keystroke before it
+    fn timing_rows(&self) -> Vec<KeyTiming> {
+        let previous = iter::once(None).chain(self.keystroke_log.iter().map(Some));
+        self.keystroke_log
+            .iter()
+            .zip(previous)
+            .map(|(keystroke, previous)| KeyTiming {
+                expected: keystroke.expected,
+                typed: keystroke.typed,
+                correct: keystroke.expected == keystroke.typed,
+                interval: previous.map(|previous| keystroke.at - previous.at),
+            })
+            .collect()
+    }
+
+    /// Whether the whole current word is typed and the next word starts with the character
+    /// Relaxed spacing uses this to insert the space the user forgot
+    fn starts_next_word(&self, pressed_char: char) -> bool {
+        let cursor_word = self.get_cursor_word();
+        if cursor_word.cursor_idx != cursor_word.word.og_len {
+            return false;
         }
-        word.get_styled_word()
+        self.words.get(self.word_idx + 1).is_some_and(|next_word| {
+            next_word
+                .word
+                .chars
+                .get(next_word.char_idx(self.reverse))
+                .is_some_and(|ch| ch.get_char_data() == pressed_char)
+        })
     }
 
     fn handle_key_press(&mut self, pressed_char: char) {
+        if self.relaxed_spacing && self.starts_next_word(pressed_char) {
+            // The space was never typed, the next word's first character took its place
+            self.advance_word(TypedState::Mistype);
+        }
+        self.log_keystroke(pressed_char);
+        let now = time::Instant::now();
+        let interval = self.last_keystroke.replace(now).map(|last| now - last);
         // implicit assumption that there is always a valid word that the cursor is on
-        let CursorWord { word, cursor_idx } = self.words.get_mut(self.word_idx).unwrap();
-        if let Some(ch) = word.get_mut_ch(*cursor_idx) {
-            let data = ch.get_char_data();
-            if data == pressed_char {
-                ch.switch_typed_state(TypedState::Correct);
-            } else {
-                ch.switch_typed_state(TypedState::Mistype);
+        let char_idx = self.get_cursor_word().char_idx(self.reverse);
+        let CursorWord {
+            word,
+            cursor_idx,
+            separator,
+        } = self.words.get_mut(self.word_idx).unwrap();
+        let expected = word.chars.get(char_idx).map(StyledChar::get_char_data);
+        let mistake = Mistake {
+            word_idx: self.word_idx,
+            char_idx,
+            typed: pressed_char,
+            expected,
+        };
+        // Nothing is typed past the end of the word, the caret waits for a space. The key was
+        // typed where the space goes, so the space is a mistake even once it is typed
+        if self.freeze_on_error && expected.is_none() {
+            separator.switch_typed_state(TypedState::Mistype);
+            self.mistakes_made += 1;
+            self.wrong_attempts += 1;
+            self.keystrokes += 1;
+            self.last_mistake = Some(mistake);
+            return;
+        }
+        match word.type_char(char_idx, pressed_char) {
+            None => return,
+            Some(TypedState::Correct) => {
+                self.correct_typed_at.push(now);
+                if let (Some(interval), true) = (interval, self.speed_gradient) {
+                    word.chars[char_idx].switch_to_timed_correct(
+                        interval,
+                        &self.theme,
+                        self.color_support,
+                    );
+                }
             }
-        } else {
-            if (word.chars.len() + 1) - word.og_len > EXTRA_CHAR_BOUNDARY {
-                return;
+            Some(_) => {
+                self.mistakes_made += 1;
+                self.last_mistake = Some(mistake);
+                // The caret waits on the mistyped character for the right key
+                if self.freeze_on_error {
+                    self.wrong_attempts += 1;
+                    self.keystrokes += 1;
+                    return;
+                }
             }
-            // word.chars should always contains the original characters
-            assert!(word.chars.len() >= word.og_len);
-            word.append_char(StyledChar::new_bad_char(pressed_char));
         }
         *cursor_idx += 1;
+        self.keystrokes += 1;
+    }
+
+    /// Number of original characters that are currently typed correctly, never counting spaces
+    fn correct_letters(&self) -> usize {
+        self.words
+            .iter()
+            .flat_map(|cursor_word| &cursor_word.word.chars)
+            .filter(|ch| ch.get_typed_state() == TypedState::Correct)
+            .count()
+    }
+
+    /// Number of original characters that are currently typed correctly
+    /// When counting spaces, the space after every finished word typed perfectly counts too
+    fn correct_chars(&self) -> usize {
+        let chars = self.correct_letters();
+        if !self.count_spaces {
+            return chars;
+        }
+        let spaces = self.words[..self.word_idx]
+            .iter()
+            .filter(|cursor_word| cursor_word.word.correctness() == Correctness::Perfect)
+            .count();
+        chars + spaces
+    }
+
+    /// Number of mistyped and extra characters still showing, up to and including the word
+    /// being typed. Fixing a mistake takes it off the count
+    fn visible_error_count(&self) -> usize {
+        let reached = cmp::min(self.word_idx + 1, self.words.len());
+        self.words[..reached]
+            .iter()
+            .flat_map(|cursor_word| &cursor_word.word.chars)
+            .filter(|ch| {
+                matches!(
+                    ch.get_typed_state(),
+                    TypedState::Mistype | TypedState::MistypeExtra
+                )
+            })
+            .count()
+    }
+
+    /// Every word the user has reached, including the one being typed
+    fn word_details(&self) -> Vec<WordDetail> {
+        let reached = cmp::min(self.word_idx + 1, self.words.len());
+        self.words[..reached]
+            .iter()
+            .map(|CursorWord { word, .. }| WordDetail {
+                target: word.chars[..word.og_len]
+                    .iter()
+                    .map(StyledChar::get_char_data)
+                    .collect(),
+                extra: word.chars[word.og_len..]
+                    .iter()
+                    .map(StyledChar::get_char_data)
+                    .collect(),
+                perfect: word.correctness() == Correctness::Perfect,
+            })
+            .collect()
     }
 
     /// User is attempting to delete a character from the type list
@@ -152,15 +1274,26 @@ impl UserCursor {
         }
 
         // At the beginning of the word
-        // Move to the previous word
+        // Move to the previous word, which is no longer finished
         if cursor_idx == 0 {
             self.word_idx -= 1;
+            self.words[self.word_idx]
+                .separator
+                .switch_typed_state(TypedState::Untyped);
+            if self.get_cursor_word().word.correctness() == Correctness::Perfect {
+                self.correct_words -= 1;
+            }
             return;
         }
 
-        let CursorWord { word, cursor_idx } = self.words.get_mut(self.word_idx).unwrap();
+        let reverse = self.reverse;
+        let cursor_word = self.words.get_mut(self.word_idx).unwrap();
 
-        *cursor_idx -= 1;
+        cursor_word.cursor_idx -= 1;
+        let char_idx = cursor_word.char_idx(reverse);
+        let CursorWord {
+            word, cursor_idx, ..
+        } = cursor_word;
 
         if *cursor_idx >= word.og_len {
             // Delete the extra character from the stream.
@@ -168,52 +1301,211 @@ impl UserCursor {
             word.chars.pop();
         } else {
             // The character must still exist as we are under the word length
-            word.get_mut_ch(*cursor_idx)
-                .unwrap()
-                .switch_typed_state(TypedState::Untyped);
+            let ch = word.get_mut_ch(char_idx).unwrap();
+            // Characters are erased in the reverse order they were typed, so the last time
+            // recorded is the one of this character
+            if ch.get_typed_state() == TypedState::Correct {
+                self.correct_typed_at.pop();
+            }
+            ch.switch_typed_state(TypedState::Untyped);
+        }
+    }
+
+    /// Erase everything typed in the current word
+    /// At the start of a word the caret moves back and the previous word is erased instead
+    fn handle_delete_word(&mut self) {
+        self.handle_delete();
+        while self.get_cursor_word().cursor_idx > 0 {
+            self.handle_delete();
         }
     }
 }
 
 enum TypingEvent {
-    AFK,
-    // TODO: need a way to
-    // join on a handler or supply information that game is done
-    TYPED(Arc<AtomicIsize>, thread::JoinHandle<()>),
+    Afk,
+    Typed(TypingClock),
 }
 
-/// Speed Typing Test Application
-///
-/// High Level Logic for Rendering the Terminal Typing Application onto Terminal
-/// Receives different keypress events from users and forwards events to the Cursor to handle logic
-struct App {
-    user_typing: TypingEvent,
-    should_exit: bool,
-    cursor: UserCursor,
-    layout: Layout,
-    // count: Arc<AtomicIsize>,
+/// Measures the time spent on the test
+/// The clock stops while the test is paused, so time spent away from the test is not counted
+#[derive(Debug)]
+struct TypingClock {
+    banked: time::Duration, // Time counted before the clock was last paused
+    running_since: Option<time::Instant>, // When the clock last started running. None if paused
 }
 
-impl App {
-    // const TICK_RATE: Duration = Duration::from_secs(1);
+impl TypingClock {
+    fn start() -> Self {
+        Self {
+            banked: time::Duration::ZERO,
+            running_since: Some(time::Instant::now()),
+        }
+    }
+
+    fn pause(&mut self) {
+        if let Some(running_since) = self.running_since.take() {
+            self.banked += running_since.elapsed();
+        }
+    }
+
+    fn resume(&mut self) {
+        self.running_since.get_or_insert_with(time::Instant::now);
+    }
+
+    fn elapsed(&self) -> time::Duration {
+        self.elapsed_at(time::Instant::now())
+    }
+
+    /// The time on the clock at an earlier instant
+    /// Instants from before the clock was last resumed are counted as the moment it resumed
+    fn elapsed_at(&self, instant: time::Instant) -> time::Duration {
+        match self.running_since {
+            Some(running_since) => self.banked + instant.saturating_duration_since(running_since),
+            None => self.banked,
+        }
+    }
+}
+
+/// The caret moving from one (word index, cursor index) position to another
+/// Used to animate the smooth caret
+#[derive(Debug, Clone, Copy)]
+struct CaretMotion {
+    from: (usize, usize),
+    to: (usize, usize),
+    moved_at: time::Instant,
+}
+
+/// The screen the application is currently showing
+#[derive(Debug, PartialEq, Eq)]
+enum Screen {
+    PreStart, // Preview of the words before the test begins
+    Typing,
+    Results,
+}
+
+/// Speed Typing Test Application
+///
+/// High Level Logic for Rendering the Terminal Typing Application onto Terminal
+/// Receives different keypress events from users and forwards events to the Cursor to handle logic
+struct App {
+    user_typing: TypingEvent,
+    should_exit: bool,
+    cursor: UserCursor,
+    layout: Layout,
+    // count: Arc<AtomicIsize>,
+    config: Config,
+    screen: Screen,
+    completed_at: Option<time::Instant>, // When the test was finished, used to flash the border
+    preset_idx: Option<usize>,           // Position in TIME_PRESETS, None for a custom limit
+    failure: Option<Mistake>,            // The mistake that went over the mistake budget
+    started_at: Option<time::Instant>,   // When the user typed their first key
+    started_at_local: Option<DateTime<Local>>, // Wall clock time of the first key
+    result: Option<RunResult>,           // Set once the test is finished
+    personal_best: Option<f64>,          // Best wpm from the history, paces the ghost
+    caret_motion: CaretMotion,           // Last move of the caret, for the smooth caret
+    checkpoints: Vec<(usize, f64)>,      // Words finished and the wpm at that point
+    show_help: bool,                     // The help overlay is open and the test is paused
+    last_autosave: Option<time::Instant>, // When progress was last snapshotted
+    autosave_write: Option<thread::JoinHandle<io::Result<()>>>, // The latest snapshot write
+    notice: Option<String>,              // Message shown in the status bar until the next key press
+    comparison: Option<Comparison>,      // Change since the previous test. None for the first test
+    clicker: Option<Clicker>,            // Plays a sound on every keystroke when sound is on
+    segments: Vec<RunResult>,            // Earlier segments this test continues from, oldest first
+    time_ran_out: bool,                  // The test was ended by the time limit
+    session: SessionCount,               // Typed in the earlier tests since launch
+    focus_lost: bool,                    // The terminal is not focused, so the test is paused
+    results_at: Option<time::Instant>,   // When the results screen was shown
+}
+
+impl App {
+    const TICK_RATE: time::Duration = time::Duration::from_millis(50);
+    const SMOOTH_TICK_RATE: time::Duration = time::Duration::from_millis(16);
+    const PAUSE_NOTICE: &str = "Paused until the terminal is focused again";
 
     /// Create a new instance of the app.
-    fn new(words: Vec<&String>) -> Self {
+    fn new(
+        words: Vec<&String>,
+        refill_pool: Vec<String>,
+        personal_best: Option<f64>,
+        config: Config,
+    ) -> Self {
         let layout = Layout::vertical([Constraint::Percentage(10), Constraint::Percentage(90)]);
+        let theme = downgrade_theme(config.theme, config.color_support);
+        // Only warn about colors the user picked, the default theme is fitted quietly
+        let theme_notice = (theme != config.theme && config.theme != Theme::default()).then(|| {
+            String::from("The terminal can not show some theme colors, using the closest ones")
+        });
+        // Fixed text is meant to be short, only a drawn test can come up short
+        let notice = (config.text.is_none() && words.len() < config.word_count).then(|| {
+            format!(
+                "Only {} of {} words are in the pool, use --repeat-words to repeat them",
+                words.len(),
+                config.word_count
+            )
+        });
+        let notice = notice.or(theme_notice);
         Self {
-            user_typing: TypingEvent::AFK,
+            user_typing: TypingEvent::Afk,
             should_exit: false,
+            screen: if config.preview {
+                Screen::PreStart
+            } else {
+                Screen::Typing
+            },
+            completed_at: None,
+            failure: None,
+            started_at: None,
+            started_at_local: None,
+            result: None,
+            personal_best,
+            checkpoints: Vec::new(),
+            show_help: false,
+            last_autosave: None,
+            autosave_write: None,
+            notice,
+            comparison: None,
+            segments: Vec::new(),
+            time_ran_out: false,
+            session: SessionCount::default(),
+            focus_lost: false,
+            results_at: None,
+            clicker: config.sound.then(Clicker::new),
+            caret_motion: CaretMotion {
+                from: (0, 0),
+                to: (0, 0),
+                moved_at: time::Instant::now(),
+            },
+            preset_idx: TIME_PRESETS
+                .iter()
+                .position(|&preset| preset == config.time_limit),
             cursor: UserCursor {
                 word_idx: 0,
-                words: words
-                    .into_iter()
-                    .map(|str| CursorWord {
-                        word: StyledWord::from_string(&str),
-                        cursor_idx: 0,
-                    })
-                    .collect(),
+                words: words.into_iter().map(|str| CursorWord::new(str)).collect(),
+                refill_pool,
+                speed_gradient: config.speed_gradient,
+                last_keystroke: None,
+                word_heat: config.word_heat,
+                mistakes_made: 0,
+                accessible: config.accessible,
+                keystrokes: 0,
+                smooth_caret: config.smooth_caret,
+                last_mistake: None,
+                allow_word_skip: config.allow_word_skip,
+                reverse: config.reverse,
+                count_spaces: config.count_spaces,
+                theme,
+                correct_words: 0,
+                correct_typed_at: Vec::new(),
+                freeze_on_error: config.freeze_on_error,
+                wrong_attempts: 0,
+                keystroke_log: Vec::new(),
+                words_typed: 0,
+                color_spaces: config.color_spaces,
+                relaxed_spacing: config.relaxed_spacing,
+                color_support: config.color_support,
             },
             layout,
+            config,
         }
     }
 
@@ -224,21 +1516,77 @@ impl App {
         frame.render_widget(self, rect);
     }
 
+    /// Time spent typing so far, not counting time spent paused
+    fn elapsed(&self) -> time::Duration {
+        match &self.user_typing {
+            TypingEvent::Typed(clock) => clock.elapsed(),
+            TypingEvent::Afk => time::Duration::ZERO,
+        }
+    }
+
+    /// Wpm of the test so far, 0 before the test starts
+    fn live_wpm(&self) -> f64 {
+        wpm(self.cursor.correct_chars(), self.elapsed())
+    }
+
+    /// Highest wpm the user reached over any window of the given length
+    /// A test shorter than the window has no full window, so its peak is the wpm of the test
+    fn peak_wpm(&self, window: time::Duration, result: &RunResult) -> f64 {
+        peak_wpm(&self.cursor.correct_typed_at, window).unwrap_or(result.wpm)
+    }
+
+    /// Keys pressed for every character typed correctly, 1 when nothing was mistyped
+    fn attempts_per_char(&self) -> f64 {
+        let correct = self.cursor.correct_typed_at.len();
+        if correct == 0 {
+            return 0.0;
+        }
+        (correct + self.cursor.wrong_attempts) as f64 / correct as f64
+    }
+
+    /// How far the live wpm is above the target, negative when behind. None without a target
+    fn pace_delta(&self) -> Option<f64> {
+        self.config
+            .target_wpm
+            .map(|target| self.live_wpm() - target)
+    }
+
+    /// Keys and words typed since launch, including the test in progress
+    fn session_count(&self) -> SessionCount {
+        SessionCount {
+            keys: self.session.keys + self.cursor.key_presses(),
+            words: self.session.words + self.cursor.words_typed,
+        }
+    }
+
+    /// Keystrokes per second so far, 0 before the test starts
+    fn kps(&self) -> f64 {
+        kps(self.cursor.key_presses(), self.elapsed())
+    }
+
+    /// Whole seconds left before the time limit runs out
     fn get_countdown(&self) -> Option<isize> {
-        if let TypingEvent::TYPED(counter, _) = &self.user_typing {
-            let count = counter.load(Ordering::Acquire);
-            return Some(count);
+        let time_limit = self.config.time_limit?;
+        if let TypingEvent::Typed(clock) = &self.user_typing {
+            return Some(time_limit - clock.elapsed().as_secs() as isize);
         }
         None
     }
     fn is_typing_time_done(&self) -> bool {
         if let Some(elapsed) = self.get_countdown() {
-            return elapsed == 0;
+            return elapsed <= 0;
         }
 
         false
     }
 
+    /// Corrected mistakes still count against the budget since they were still made
+    fn is_over_mistake_budget(&self) -> bool {
+        self.config
+            .max_mistakes
+            .is_some_and(|budget| self.cursor.mistakes_made > budget)
+    }
+
     /// Run the app until the user exits.
     fn run(&mut self, mut terminal: DefaultTerminal) -> Result<()> {
         while !self.should_exit {
@@ -246,54 +1594,819 @@ impl App {
                 self.draw(frame);
             })?;
             self.handle_events()?;
+            self.track_caret();
+            self.autosave();
             // Because in immediate mode, we need to manually check the time ourselves
             // the precision will not be off by much if we manually check
             // Checking async is not too helpful because of these facts
-            self.should_exit =
-                self.should_exit || self.cursor.is_game_done() || self.is_typing_time_done();
-            if self.should_exit {
-                self.user_typing = TypingEvent::AFK;
+            if self.screen == Screen::Typing && self.should_finish() {
+                self.finish()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the test is over according to the completion mode
+    /// Going over the mistake budget always ends the test
+    /// With a correct word target the words are done once enough of them are typed perfectly,
+    /// and with a character limit once enough characters are typed correctly, even in the
+    /// middle of a word
+    fn should_finish(&self) -> bool {
+        if self.is_over_mistake_budget() {
+            return true;
+        }
+        let words_done = match (self.config.correct_target, self.config.char_limit) {
+            (None, None) => self.cursor.is_game_done(),
+            (correct_target, char_limit) => {
+                correct_target.is_some_and(|target| self.cursor.correct_words >= target)
+                    || char_limit.is_some_and(|limit| self.cursor.correct_letters() >= limit)
             }
+        };
+        match self.config.completion {
+            CompletionMode::WordsOnly => words_done,
+            CompletionMode::TimeOnly => self.is_typing_time_done(),
+            CompletionMode::Either => words_done || self.is_typing_time_done(),
+        }
+    }
+
+    /// Snapshot the progress of the test once every autosave interval
+    /// The snapshot is written on another thread so a slow disk never holds up a frame. Saving
+    /// is best effort, a failed write is skipped and tried again on the next interval
+    fn autosave(&mut self) {
+        let Some(interval) = self.config.autosave_interval else {
+            return;
+        };
+        if self.screen != Screen::Typing || !self.has_started() {
+            return;
+        }
+        let now = time::Instant::now();
+        let last_autosave = *self.last_autosave.get_or_insert(now);
+        if now - last_autosave < interval {
+            return;
+        }
+        self.last_autosave = Some(now);
+        let snapshot = ProgressSnapshot {
+            elapsed: self.elapsed(),
+            chars_typed: self.cursor.key_presses(),
+            correct_chars: self.cursor.correct_chars(),
+            words_done: self.cursor.word_idx,
+            mistakes: self.cursor.mistakes_made,
+        };
+        let path = self.config.autosave_file.clone();
+        self.autosave_write = Some(thread::spawn(move || {
+            fs::write(path, snapshot.to_json() + "\n")
+        }));
+    }
+
+    /// Transition from the typing test onto the results screen
+    /// Called exactly once when the test is done, not on every frame of the results screen
+    fn finish(&mut self) -> Result<()> {
+        let duration = self.typed_duration();
+        self.time_ran_out = self.is_typing_time_done();
+        self.user_typing = TypingEvent::Afk;
+        self.screen = Screen::Results;
+        self.results_at = Some(time::Instant::now());
+        if self.is_over_mistake_budget() {
+            self.failure = self.cursor.last_mistake;
+        }
+
+        let result = RunResult {
+            timestamp: self.started_at_local.unwrap_or_else(Local::now),
+            duration,
+            wpm: wpm(self.cursor.correct_chars(), duration),
+            accuracy: accuracy(self.cursor.mistakes_made, self.cursor.keystrokes),
+        };
+        // The previous test has to be read before this one is added after it
+        // The comparison is only extra, a history that can not be read skips it
+        match load_history(&self.config.history_file) {
+            Ok(mut history) => {
+                self.comparison = history
+                    .pop()
+                    .map(|previous| compare_to_previous(&previous, &result));
+            }
+            Err(err) => {
+                self.notice = Some(format!("Could not compare with the last test: {err}"));
+            }
+        }
+        if self.config.save_history {
+            append_history(&self.config.history_file, &result)?;
+        }
+        if let Some(export_file) = &self.config.export_file {
+            let detail = RunDetail {
+                result: result.clone(),
+                words: self.cursor.word_details(),
+            };
+            write_export(export_file, &detail, self.config.private_export)?;
+        }
+        if let Some(timing_export_file) = &self.config.timing_export_file {
+            write_timing_export(timing_export_file, &self.cursor.timing_rows())?;
+        }
+        self.result = Some(result);
+        // The finished test is saved properly, the snapshot is no longer needed
+        // Wait for a write in flight so it does not recreate the file after it is removed
+        if let Some(write) = self.autosave_write.take() {
+            let _ = write.join();
+        }
+        if self.last_autosave.is_some() {
+            match fs::remove_file(&self.config.autosave_file) {
+                Err(err) if err.kind() != ErrorKind::NotFound => {
+                    return Err(err).wrap_err("Failed to remove the autosave file")
+                }
+                _ => {}
+            }
+        }
+
+        if self.config.complete_signal {
+            let mut stdout = io::stdout();
+            stdout.write_all(b"\x07")?;
+            stdout.flush()?;
+            self.completed_at = Some(time::Instant::now());
         }
         Ok(())
     }
 
+    /// How long the user spent on the test
+    /// A test that ran out of time lasted exactly the time limit, even though the timer is only
+    /// checked once a tick. Otherwise the test ended on the user's last keystroke
+    fn typed_duration(&self) -> time::Duration {
+        let TypingEvent::Typed(clock) = &self.user_typing else {
+            return time::Duration::ZERO;
+        };
+        let ended_on_time = self.config.completion != CompletionMode::WordsOnly;
+        match self.config.time_limit {
+            Some(time_limit) if ended_on_time && self.is_typing_time_done() => {
+                time::Duration::from_secs(time_limit as u64)
+            }
+            _ => clock.elapsed_at(
+                self.cursor
+                    .last_keystroke
+                    .unwrap_or_else(time::Instant::now),
+            ),
+        }
+    }
+
+    /// The test starts on the first key the user types
+    fn has_started(&self) -> bool {
+        self.started_at.is_some()
+    }
+
+    /// Wait less between frames while the smooth caret is on so the animation is not choppy
+    fn tick_rate(&self) -> time::Duration {
+        if self.config.smooth_caret {
+            Self::SMOOTH_TICK_RATE
+        } else {
+            Self::TICK_RATE
+        }
+    }
+
+    /// Start a new caret animation whenever the caret moves
+    fn track_caret(&mut self) {
+        let Some(cursor_word) = self.cursor.words.get(self.cursor.word_idx) else {
+            return;
+        };
+        let caret = (
+            self.cursor.word_idx,
+            cursor_word.char_idx(self.cursor.reverse),
+        );
+        if caret != self.caret_motion.to {
+            self.caret_motion = CaretMotion {
+                from: self.caret_motion.to,
+                to: caret,
+                moved_at: time::Instant::now(),
+            };
+        }
+    }
+
+    /// Where to draw the smooth caret relative to the text area
+    ///
+    /// The caret glides horizontally from its previous cell to the current one over
+    /// SMOOTH_CARET_DURATION, landing on whichever cell is closest to its fractional position.
+    /// When the move crosses a line break there is no sensible path to glide along, so the
+    /// caret snaps straight to the new line
+    fn smooth_caret_position(&self, positions: &[(usize, usize)]) -> Option<(usize, usize)> {
+        let cell = |(word_idx, cursor_idx): (usize, usize)| {
+            positions
+                .get(word_idx)
+                .map(|&(row, col)| (row, col + cursor_idx))
+        };
+        let CaretMotion { from, to, moved_at } = self.caret_motion;
+        let (to_row, to_col) = cell(to)?;
+        let Some((_, from_col)) = cell(from).filter(|&(row, _)| row == to_row) else {
+            return Some((to_row, to_col));
+        };
+        let progress =
+            (moved_at.elapsed().as_secs_f64() / SMOOTH_CARET_DURATION.as_secs_f64()).min(1.0);
+        let col = from_col as f64 + (to_col as f64 - from_col as f64) * progress;
+        Some((to_row, col.round() as usize))
+    }
+
+    /// Number of characters the personal best would have typed by now
+    /// Spaces are not counted, the same as when calculating wpm
+    fn ghost_position(&self) -> Option<usize> {
+        let personal_best = self.personal_best?;
+        self.started_at?;
+        let minutes = self.elapsed().as_secs_f64() / 60.0;
+        Some((personal_best * CHARS_PER_WORD * minutes) as usize)
+    }
+
+    /// The word and character index the ghost is currently on
+    fn ghost_cell(&self) -> Option<(usize, usize)> {
+        let mut remaining = self.ghost_position()?;
+        for (word_idx, cursor_word) in self.cursor.words.iter().enumerate() {
+            if remaining < cursor_word.word.og_len {
+                return Some((word_idx, remaining));
+            }
+            remaining -= cursor_word.word.og_len;
+        }
+        None
+    }
+
+    /// Mark the ghost's cell with a faint background
+    /// The ghost never adds modifiers so it can not be mistaken for the user's caret, and it
+    /// is hidden entirely when it is on the same cell as the caret
+    fn add_ghost(&self, idx: usize, spans: &mut [Span<'_>], ghost_cell: Option<(usize, usize)>) {
+        let Some((ghost_word, ghost_char)) = ghost_cell else {
+            return;
+        };
+        let cursor_word = self.cursor.get_cursor_word();
+        let on_caret = ghost_word == self.cursor.word_idx
+            && ghost_char == cursor_word.char_idx(self.cursor.reverse);
+        if idx != 2 * ghost_word || on_caret {
+            return;
+        }
+        if let Some(span) = spans.get_mut(ghost_char) {
+            span.style = span.style.bg(GHOST_BG);
+        }
+    }
+
+    /// Show mistakes in the neutral untyped color until the grace period has passed
+    /// Every character has its own grace period. Typing the next character quickly neither
+    /// reveals the previous mistake early nor extends its grace, and a mistake that is erased
+    /// within its grace period is never shown at all
+    fn add_grace(&self, idx: usize, cursor_word: &CursorWord, spans: &mut [Span<'_>]) {
+        let Some(grace) = self.config.mistake_grace else {
+            return;
+        };
+        // A space typed out of place is a mistake like any character, and the caret can be
+        // waiting on it, so only its colors are put back
+        if !idx.is_multiple_of(2) {
+            let separator = self.cursor.words.get(idx / 2).map(|word| &word.separator);
+            if let (Some(span), Some(separator)) = (spans.first_mut(), separator) {
+                if separator.is_fresh_mistake(grace) {
+                    span.style = span.style.patch(self.cursor.theme.untyped);
+                }
+            }
+            return;
+        }
+        for (span, ch) in spans.iter_mut().zip(&cursor_word.word.chars) {
+            if ch.is_fresh_mistake(grace) {
+                span.style = span.style.patch(self.cursor.theme.untyped);
+            }
+        }
+    }
+
+    /// Style a word or separator for the current screen
+    fn style_for_screen<'a>(
+        &'a self,
+        idx: usize,
+        cursor_word: &'a CursorWord,
+        ghost_cell: Option<(usize, usize)>,
+    ) -> Vec<Span<'a>> {
+        match self.screen {
+            Screen::Typing => {
+                let mut spans = self.cursor.style_word(idx, cursor_word);
+                self.add_ghost(idx, &mut spans, ghost_cell);
+                self.add_grace(idx, cursor_word, &mut spans);
+                spans
+            }
+            Screen::PreStart | Screen::Results => match self.cursor.separator_style(idx) {
+                Some(style) => vec![Span::styled(" ", style)],
+                None => cursor_word.word.get_styled_word(&self.cursor.theme),
+            },
+        }
+    }
+
+    /// Show the word the test failed on with the offending character highlighted, then the
+    /// expected and typed characters side by side
+    /// A substitution highlights the wrong character inside the word. A character typed past
+    /// the end of the word is shown appended to the word, since a space was expected there
+    fn failure_diff(&self, failure: &Mistake) -> Vec<Line<'_>> {
+        let word = &self.cursor.words[failure.word_idx].word;
+        let highlight = Style::new().add_modifier(Modifier::REVERSED | Modifier::BOLD);
+        let mut spans: Vec<Span<'_>> = word.chars[..word.og_len]
+            .iter()
+            .enumerate()
+            .map(|(idx, ch)| {
+                let span = Span::raw(ch.get_char_data().to_string());
+                if idx == failure.char_idx {
+                    span.style(highlight.patch(self.cursor.theme.mistype))
+                } else {
+                    span
+                }
+            })
+            .collect();
+        if failure.expected.is_none() {
+            spans.push(Span::styled(
+                failure.typed.to_string(),
+                highlight.patch(self.cursor.theme.mistype_extra),
+            ));
+        }
+        let expected = match failure.expected {
+            Some(expected) => format!("'{expected}'"),
+            None => String::from("a space"),
+        };
+        vec![
+            Line::from(format!(
+                "Failed on word {} at character {}",
+                failure.word_idx + 1,
+                failure.char_idx + 1
+            )),
+            Line::from(spans),
+            Line::from(format!("Expected {expected}, typed '{}'", failure.typed)),
+        ]
+    }
+
+    fn results_summary(&self) -> String {
+        let headline = if self.failure.is_some() {
+            "Too many mistakes!"
+        } else {
+            "Test complete!"
+        };
+        if self.can_continue() {
+            format!("{headline} Press c to type the rest, l to level up or Esc to exit")
+        } else {
+            format!("{headline} Press l to level up or Esc to exit")
+        }
+    }
+
+    /// Statistics of the finished test shown on the results screen
+    fn results_lines(&self, result: &RunResult) -> Vec<Line<'_>> {
+        let saved = if self.config.save_history {
+            ""
+        } else {
+            " (not saved)"
+        };
+        // Raw wpm counts every keystroke, right or wrong
+        let raw_wpm = wpm(self.cursor.keystrokes, result.duration);
+        let kps = if self.config.show_kps {
+            format!(
+                ", {:.1} kps",
+                kps(self.cursor.key_presses(), result.duration)
+            )
+        } else {
+            String::new()
+        };
+        let mut lines = vec![
+            Line::from(format!(
+                "{:.0} wpm ({raw_wpm:.0} raw){kps}, {:.1}% accuracy{saved}",
+                result.wpm, result.accuracy
+            )),
+            Line::from(format!(
+                "Peak {:.0} wpm over {}s",
+                self.peak_wpm(PEAK_WINDOW, result),
+                PEAK_WINDOW.as_secs()
+            )),
+            Line::from(format!(
+                "Started {}, typed for {:.1}s",
+                result.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                result.duration.as_secs_f64()
+            )),
+        ];
+        if let Some(comparison) = self.comparison {
+            lines.push(comparison_line(comparison));
+        }
+        if !self.segments.is_empty() {
+            let segments = self.segments.iter().chain(iter::once(result));
+            let duration: time::Duration = segments.clone().map(|segment| segment.duration).sum();
+            // Each segment counts for as long as it was typed
+            let combined_wpm = segments
+                .map(|segment| segment.wpm * segment.duration.as_secs_f64())
+                .sum::<f64>()
+                / duration.as_secs_f64();
+            lines.push(Line::from(format!(
+                "All {} segments: {combined_wpm:.0} wpm over {:.1}s",
+                self.segments.len() + 1,
+                duration.as_secs_f64()
+            )));
+        }
+        if self.config.freeze_on_error {
+            lines.push(Line::from(format!(
+                "{:.2} attempts per character, {} wrong",
+                self.attempts_per_char(),
+                self.cursor.wrong_attempts
+            )));
+        }
+        if !self.checkpoints().is_empty() {
+            let checkpoints: Vec<_> = self
+                .checkpoints()
+                .iter()
+                .map(|(words_done, wpm)| format!("{words_done} words: {wpm:.0} wpm"))
+                .collect();
+            lines.push(Line::from(format!(
+                "Checkpoints: {}",
+                checkpoints.join(" | ")
+            )));
+        }
+        lines
+    }
+
+    /// Toggle a warm-up test that is not recorded in the history
+    /// Like the time limit, this is locked in once the user starts typing
+    fn toggle_save_history(&mut self) {
+        if !self.has_started() {
+            self.config.save_history = !self.config.save_history;
+        }
+    }
+
+    /// Switch to the next time limit preset
+    /// The time limit is locked in once the user starts typing
+    fn cycle_time_preset(&mut self) {
+        if self.has_started() {
+            return;
+        }
+        let mut next = self
+            .preset_idx
+            .map_or(0, |idx| (idx + 1) % TIME_PRESETS.len());
+        // A test that only finishes on time can not go without a time limit
+        if self.config.completion == CompletionMode::TimeOnly && TIME_PRESETS[next].is_none() {
+            next = (next + 1) % TIME_PRESETS.len();
+        }
+        self.preset_idx = Some(next);
+        self.config.time_limit = TIME_PRESETS[next];
+        // A preset picked with Tab is remembered like one passed with --time
+        self.config.settings.time_limit = Some(self.config.time_limit);
+        self.config.settings.completion = Some(self.config.completion);
+    }
+
+    /// Wpm recorded every CHECKPOINT_INTERVAL words of an endurance test
+    fn checkpoints(&self) -> &[(usize, f64)] {
+        &self.checkpoints
+    }
+
+    /// Record a checkpoint when the user finishes a multiple of CHECKPOINT_INTERVAL words
+    /// Only done on a space press, so the render never has to count characters. A word that
+    /// is deleted back into and finished again does not record its checkpoint twice
+    fn record_checkpoint(&mut self) {
+        let words_done = self.cursor.word_idx;
+        let already_recorded = self
+            .checkpoints
+            .last()
+            .is_some_and(|&(recorded, _)| recorded >= words_done);
+        if !self.config.endurance
+            || words_done == 0
+            || !words_done.is_multiple_of(CHECKPOINT_INTERVAL)
+            || already_recorded
+        {
+            return;
+        }
+        self.checkpoints
+            .push((words_done, wpm(self.cursor.correct_chars(), self.elapsed())));
+    }
+
     fn handle_char_read(&mut self, ch: char) {
+        // An ignored space should not start the test either
+        if ch == ' ' && !self.cursor.can_advance() {
+            return;
+        }
+        if self.started_at.is_none() {
+            self.started_at = Some(time::Instant::now());
+            self.started_at_local = Some(Local::now());
+        }
+        let mistakes_made = self.cursor.mistakes_made;
         if ch == ' ' {
             self.cursor.handle_space_press();
         } else {
             self.cursor.handle_key_press(ch);
         }
-        if !matches!(self.user_typing, TypingEvent::AFK) {
-            return;
+        if let Some(clicker) = &self.clicker {
+            clicker.click(self.cursor.mistakes_made > mistakes_made);
+        }
+        // Relaxed spacing can finish a word without a space
+        self.record_checkpoint();
+        if matches!(self.user_typing, TypingEvent::Afk) {
+            self.user_typing = TypingEvent::Typed(TypingClock::start());
         }
-        let atom_counter = Arc::new(AtomicIsize::new(COUNTDOWN));
-        let counter: Arc<AtomicIsize> = Arc::clone(&atom_counter);
-        let timer_thread = thread::spawn(move || {
-            let second_delay = time::Duration::from_secs(1);
-            // TODO: Have a way to check if the game is over
-            // so the thread can be killed and dropped
-            while counter.load(Ordering::Acquire) > 0 {
-                thread::sleep(second_delay);
-                counter.fetch_add(-1, Ordering::Release);
+    }
+
+    /// Read the word files again and start a new test with words drawn from them
+    /// If the files can no longer be read, or no words are left in them, the current test is
+    /// kept and the error is shown instead
+    fn reload_words(&mut self) {
+        match self.restart(self.config.clone()) {
+            Ok(pool_len) => {
+                self.notice = Some(format!("Reloaded the words, {pool_len} in the pool"));
             }
+            Err(err) => self.notice = Some(format!("Could not reload the words: {err}")),
+        }
+    }
+
+    /// Start a new test with the config, drawing from a freshly loaded pool
+    /// The current test is kept if the words can not be loaded. Returns the size of the pool
+    fn restart(&mut self, config: Config) -> Result<usize> {
+        let pool = load_pool(&config)?;
+        let (rand_words, refill_pool) = draw_words(&pool, &config);
+        let session = self.session_count();
+        *self = App::new(rand_words, refill_pool, self.personal_best, config);
+        self.session = session;
+        Ok(pool.len())
+    }
+
+    /// Whether the results were shown too recently for a key to start a new test
+    /// Esc still exits right away
+    fn is_results_locked(&self) -> bool {
+        self.screen == Screen::Results
+            && self
+                .results_at
+                .is_some_and(|results_at| results_at.elapsed() < self.config.results_delay)
+    }
+
+    /// Whether the time ran out before every word was typed, so the rest can still be typed
+    fn can_continue(&self) -> bool {
+        self.failure.is_none() && self.time_ran_out && !self.cursor.is_game_done()
+    }
+
+    /// Start a new timed segment with the words left over when the time ran out
+    /// The word being typed when the time ran out is typed again from the start
+    fn continue_test(&mut self) {
+        let Some(result) = self.result.take() else {
+            return;
+        };
+        let tail = self.cursor.untyped_tail();
+        let refill_pool = mem::take(&mut self.cursor.refill_pool);
+        let mut segments = mem::take(&mut self.segments);
+        segments.push(result);
+        let config = mem::take(&mut self.config);
+        let session = self.session_count();
+        *self = App::new(
+            tail.iter().collect(),
+            refill_pool,
+            self.personal_best,
+            config,
+        );
+        self.segments = segments;
+        self.session = session;
+        self.notice = Some(format!("Continuing with the {} words left", tail.len()));
+    }
+
+    /// Start a new test a step harder than the one just finished
+    /// The harder config is only kept once its words are loaded
+    fn level_up(&mut self) {
+        if self.config.text.is_some() {
+            self.notice = Some(String::from("Fixed text can not be made harder"));
+            return;
+        }
+        let mut config = self.config.clone();
+        let Some(change) = config.level_up() else {
+            self.notice = Some(String::from("Already at the hardest level"));
+            return;
+        };
+        self.notice = Some(match self.restart(config) {
+            Ok(_) => format!("Level up: {change}"),
+            Err(err) => format!("Could not reload the words: {err}"),
         });
-        self.user_typing = TypingEvent::TYPED(atom_counter, timer_thread);
+    }
+
+    /// Open or close the help overlay
+    /// The test is paused while the help is open so reading it does not cost any time
+    fn toggle_help(&mut self) {
+        self.show_help = !self.show_help;
+        self.sync_clock();
+    }
+
+    /// Pause the test while the terminal is not focused, and resume it once it is again
+    fn set_focused(&mut self, focused: bool) {
+        self.focus_lost = !focused;
+        // Only a running test is paused, and any other notice is left for the user to read
+        if focused {
+            if self.notice.as_deref() == Some(Self::PAUSE_NOTICE) {
+                self.notice = None;
+            }
+        } else if self.screen == Screen::Typing && self.has_started() {
+            self.notice = Some(String::from(Self::PAUSE_NOTICE));
+        }
+        self.sync_clock();
+    }
+
+    /// The clock only runs while neither the help is open nor the terminal is unfocused
+    fn sync_clock(&mut self) {
+        let paused = self.show_help || self.focus_lost;
+        if let TypingEvent::Typed(clock) = &mut self.user_typing {
+            if paused {
+                clock.pause();
+            } else {
+                clock.resume();
+            }
+        }
+    }
+
+    /// Key bindings and the settings of the current test
+    fn help_lines(&self) -> Vec<Line<'_>> {
+        let on_off = |enabled: bool| if enabled { "on" } else { "off" };
+        vec![
+            Line::from("Type the words, Space moves to the next word".bold()),
+            Line::from("Backspace         erase the last character"),
+            Line::from(format!(
+                "Delete            {}",
+                self.config.delete_key.label()
+            )),
+            Line::from("Tab               change the time limit before starting"),
+            Line::from("F2                toggle saving the test before starting"),
+            Line::from("F5                reload the word files and start a new test"),
+            Line::from("l                 start a harder test from the results"),
+            Line::from("c                 type the words left when the time ran out"),
+            Line::from("F1 or ?           open and close this help"),
+            Line::from("Esc               close this help, or exit"),
+            Line::default(),
+            Line::from("Current settings".bold()),
+            Line::from(format!(
+                "Time limit: {}",
+                time_limit_label(self.config.time_limit)
+            )),
+            Line::from(format!(
+                "Save to history: {}",
+                on_off(self.config.save_history)
+            )),
+            Line::from(format!("Finish on: {}", self.config.completion.label())),
+            Line::from(format!("Reverse: {}", on_off(self.config.reverse))),
+            Line::from(format!(
+                "Count spaces: {}",
+                on_off(self.config.count_spaces)
+            )),
+            Line::from(format!("Colors: {}", self.config.color_support.label())),
+            Line::from(format!(
+                "Max mistakes: {}",
+                self.config
+                    .max_mistakes
+                    .map_or(String::from("none"), |budget| budget.to_string())
+            )),
+        ]
     }
 
     /// Handle events from the terminal.
+    /// Waits at most a tick for an event so the screen keeps redrawing while the user is idle
     fn handle_events(&mut self) -> io::Result<()> {
-        if let Event::Key(key) = event::read()? {
+        if !event::poll(self.tick_rate())? {
+            return Ok(());
+        }
+        self.handle_event(event::read()?);
+        Ok(())
+    }
+
+    /// React to a single event from the terminal
+    fn handle_event(&mut self, event: Event) {
+        match event {
+            Event::FocusLost if self.config.pause_on_blur => self.set_focused(false),
+            Event::FocusGained if self.config.pause_on_blur => self.set_focused(true),
+            _ => {}
+        }
+        if let Event::Key(key) = event {
             if key.kind == KeyEventKind::Press {
+                // A key press means the terminal has focus, even if it never said so
+                if self.focus_lost {
+                    self.set_focused(true);
+                }
+                self.notice = None;
+                // A ? in the middle of the test is typed like any other character
+                let typing = self.screen == Screen::Typing && self.has_started();
                 match key.code {
-                    KeyCode::Char(ch) => self.handle_char_read(ch),
-                    KeyCode::Backspace | KeyCode::Delete => self.cursor.handle_delete(),
+                    KeyCode::F(1) => self.toggle_help(),
+                    KeyCode::Char('?') if self.show_help || !typing => self.toggle_help(),
+                    KeyCode::Esc if self.show_help => self.toggle_help(),
+                    // Typing is paused while the help is open
+                    _ if self.show_help => {}
                     KeyCode::Esc => self.should_exit = true,
+                    _ if self.is_results_locked() => {}
+                    KeyCode::F(5) => self.reload_words(),
+                    // The key only dismisses the preview, it is not typed
+                    _ if self.screen == Screen::PreStart => self.screen = Screen::Typing,
+                    KeyCode::Char('l') if self.screen == Screen::Results => self.level_up(),
+                    KeyCode::Char('c') if self.screen == Screen::Results && self.can_continue() => {
+                        self.continue_test()
+                    }
+                    // The test is over, only allow the user to leave, level up or continue
+                    _ if self.screen == Screen::Results => {}
+                    // Shortcuts like Ctrl+A are not typed into the test
+                    KeyCode::Char(_) if is_shortcut(key.modifiers) => {}
+                    KeyCode::Char(ch) => self.handle_char_read(ch),
+                    KeyCode::Backspace => self.cursor.handle_delete(),
+                    KeyCode::Delete => match self.config.delete_key {
+                        DeleteKey::Backspace => self.cursor.handle_delete(),
+                        DeleteKey::Word => self.cursor.handle_delete_word(),
+                        DeleteKey::Nothing => {}
+                    },
+                    // A word file loaded with --allow-tabs can expect a tab to be typed
+                    KeyCode::Tab if self.cursor.expected_char() == '\t' => {
+                        self.handle_char_read('\t')
+                    }
+                    KeyCode::Tab => self.cycle_time_preset(),
+                    KeyCode::F(2) => self.toggle_save_history(),
                     _ => {}
                 }
             }
         }
-        Ok(())
+    }
+
+    /// Whether the word is shown, or hidden for being too far from the word being typed
+    /// Every word is shown before and after the test
+    fn is_word_visible(&self, word_idx: usize) -> bool {
+        let Some(ahead) = self.config.visible_words else {
+            return true;
+        };
+        let cursor_idx = self.cursor.word_idx;
+        self.screen != Screen::Typing || (cursor_idx..=cursor_idx + ahead).contains(&word_idx)
+    }
+
+    /// Every word wrapped across the text area, scrolled to keep the active line in view
+    fn render_words(
+        &self,
+        block: Block<'_>,
+        area: Rect,
+        buf: &mut Buffer,
+        ghost_cell: Option<(usize, usize)>,
+    ) {
+        let separator = CursorWord::new(" ");
+        let text_area = block.inner(area);
+
+        // Lay the words out ourselves so the screen position of every character is known
+        let positions = wrap_words(&self.cursor.words, text_area.width as usize);
+        let mut lines: Vec<Line<'_>> = Vec::new();
+        for (word_idx, (cursor_word, &(row, _))) in
+            self.cursor.words.iter().zip(&positions).enumerate()
+        {
+            if lines.len() <= row {
+                lines.push(Line::default());
+            }
+            let line = lines.last_mut().unwrap();
+            if self.is_word_visible(word_idx) {
+                line.spans
+                    .extend(self.style_for_screen(2 * word_idx, cursor_word, ghost_cell));
+            } else {
+                // Blank out hidden words without moving the words around them
+                line.spans
+                    .push(Span::raw(" ".repeat(cursor_word.word.chars.len())));
+            }
+            line.spans
+                .extend(self.style_for_screen(2 * word_idx + 1, &separator, ghost_cell));
+        }
+
+        // Keep the line being typed in view as the text grows past the screen
+        let active_row = positions
+            .get(self.cursor.word_idx)
+            .or(positions.last())
+            .map_or(0, |&(row, _)| row);
+        let scroll = scroll_offset(
+            active_row,
+            lines.len(),
+            text_area.height as usize,
+            self.config.scroll_anchor,
+        );
+
+        // Retrieve a vector of each word in Styled Form
+        Paragraph::new(lines)
+            .block(block)
+            .left_aligned()
+            .scroll((scroll as u16, 0))
+            .render(area, buf);
+
+        if self.config.smooth_caret && self.screen == Screen::Typing {
+            if let Some((row, col)) = self.smooth_caret_position(&positions) {
+                let row = row.saturating_sub(scroll);
+                let (x, y) = (text_area.x + col as u16, text_area.y + row as u16);
+                if text_area.contains((x, y).into()) {
+                    buf[(x, y)].set_style(self.cursor.caret_style());
+                }
+            }
+        }
+    }
+
+    /// Only the word being typed, and any visible words after it, in the middle of the text area
+    /// Only the caret and coloring of the shown words change, typing works the same as always
+    fn render_flashcard(
+        &self,
+        block: Block<'_>,
+        area: Rect,
+        buf: &mut Buffer,
+        ghost_cell: Option<(usize, usize)>,
+    ) {
+        let text_area = block.inner(area);
+        block.render(area, buf);
+        let separator = CursorWord::new(" ");
+        let shown = self.config.visible_words.unwrap_or(0) + 1;
+        let mut spans = Vec::new();
+        for (word_idx, cursor_word) in self
+            .cursor
+            .words
+            .iter()
+            .enumerate()
+            .skip(self.cursor.word_idx)
+            .take(shown)
+        {
+            spans.extend(self.style_for_screen(2 * word_idx, cursor_word, ghost_cell));
+            spans.extend(self.style_for_screen(2 * word_idx + 1, &separator, ghost_cell));
+        }
+        let line = Line::from(spans);
+        let card = centered_rect(text_area, line.width() as u16, 1);
+        line.render(card, buf);
     }
 }
 
@@ -301,43 +2414,1205 @@ impl Widget for &App {
     /// Responsible for rendering just the Speed Typing test onto the screen and each of the
     /// words managed by the Cursor
     fn render(self, area: Rect, buf: &mut Buffer) {
-        // TODO: Scrolling on input would be nice
         let areas = self.layout.split(area);
-        let separator = CursorWord {
-            word: StyledWord::from_string(" "),
-            cursor_idx: 0,
+        let border_color = match self.completed_at {
+            Some(completed_at) if completed_at.elapsed() < COMPLETE_FLASH => COMPLETE_FLASH_COLOR,
+            _ => Color::Gray,
         };
+        let ghost_cell = self.ghost_cell();
+        let block = Block::bordered().fg(border_color);
+        let mut words_area = areas[1];
 
-        // Retrieve a vector of each word in Styled Form
-        Paragraph::new(
-            self.cursor
-                .words
-                .iter()
-                .intersperse(&separator)
-                .enumerate()
-                .flat_map(|(idx, cursor_word)| self.cursor.style_word(idx, cursor_word))
-                .collect::<Line<'_>>(),
-        )
-        .block(Block::bordered().gray())
-        .left_aligned()
-        .wrap(Wrap { trim: true })
-        .render(areas[1], buf);
+        if let (Screen::Results, Some(result)) = (&self.screen, &self.result) {
+            let lines = self.results_lines(result);
+            let [results_area, rest] = Layout::vertical([
+                Constraint::Length(lines.len() as u16 + 2),
+                Constraint::Min(0),
+            ])
+            .areas(words_area);
+            Paragraph::new(lines)
+                .block(title_block("Results"))
+                .render(results_area, buf);
+            words_area = rest;
+        }
+        if let (Screen::Results, Some(failure)) = (&self.screen, &self.failure) {
+            let [diff_area, rest] =
+                Layout::vertical([Constraint::Length(5), Constraint::Min(0)]).areas(words_area);
+            Paragraph::new(self.failure_diff(failure))
+                .block(Block::bordered().fg(MISTYPE_COLOR))
+                .render(diff_area, buf);
+            words_area = rest;
+        }
+        if self.config.flashcard && self.screen == Screen::Typing {
+            self.render_flashcard(block, words_area, buf, ghost_cell);
+        } else {
+            self.render_words(block, words_area, buf, ghost_cell);
+        }
 
-        let countdown = match self.get_countdown() {
-            Some(elapsed) => cmp::max(elapsed, 0),
-            None => COUNTDOWN,
+        let warm_up = if self.config.save_history {
+            ""
+        } else {
+            " warm-up, not saved (F2 to change)"
+        };
+        let status = match (&self.screen, self.get_countdown()) {
+            (Screen::PreStart, _) => String::from("Preview of the words, press any key to start"),
+            (Screen::Results, _) => self.results_summary(),
+            (Screen::Typing, Some(elapsed)) => cmp::max(elapsed, 0).to_string(),
+            (Screen::Typing, None) if !self.has_started() => format!(
+                "{} (Tab to change){warm_up}",
+                time_limit_label(self.config.time_limit)
+            ),
+            (Screen::Typing, None) => {
+                let progress = match (self.config.correct_target, self.config.char_limit) {
+                    (Some(target), _) => {
+                        format!("{}/{target} correct words", self.cursor.correct_words)
+                    }
+                    (None, Some(limit)) => {
+                        format!("{}/{limit} characters", self.cursor.correct_letters())
+                    }
+                    (None, None) => {
+                        format!("{}/{} words", self.cursor.word_idx, self.cursor.words.len())
+                    }
+                };
+                match self.checkpoints().last() {
+                    Some((_, wpm)) => format!("{progress}, last checkpoint {wpm:.0} wpm"),
+                    None => progress,
+                }
+            }
+        };
+        let status = match self.screen {
+            Screen::Typing if self.config.show_kps && self.has_started() => {
+                format!("{status} | {:.1} kps", self.kps())
+            }
+            _ => status,
         };
-        Paragraph::new(countdown.to_string())
-            .block(title_block(SPEED_TYPING_TITLE))
+        let status = match self.screen {
+            Screen::Typing if self.config.show_odometer => {
+                let session = self.session_count();
+                format!(
+                    "{status} | {}, {} this session",
+                    count_label(session.keys, "key"),
+                    count_label(session.words, "word")
+                )
+            }
+            _ => status,
+        };
+        let mut status = Line::from(status);
+        if let (Screen::Typing, true, Some(delta)) =
+            (&self.screen, self.has_started(), self.pace_delta())
+        {
+            let (color, pace) = if delta >= 0.0 {
+                (CORRECT_COLOR, "ahead")
+            } else {
+                (MISTYPE_COLOR, "behind")
+            };
+            status.push_span(" | ");
+            status.push_span(Span::styled(
+                format!("{:.0} wpm {pace}", delta.abs()),
+                Style::new().fg(color),
+            ));
+        }
+        let status = self.notice.clone().map(Line::from).unwrap_or(status);
+        let title_wpm = match (&self.screen, &self.result) {
+            _ if !self.config.wpm_in_title => None,
+            (Screen::Typing, _) => Some(self.live_wpm()),
+            (Screen::Results, Some(result)) => Some(result.wpm),
+            _ => None,
+        };
+        let title = match title_wpm {
+            Some(wpm) => format!("{SPEED_TYPING_TITLE} - {wpm:.0} wpm"),
+            None => String::from(SPEED_TYPING_TITLE),
+        };
+        let mut status_block = title_block(&title);
+        if self.config.show_error_count && self.screen == Screen::Typing {
+            let errors = self.cursor.visible_error_count();
+            let (color, noun) = match errors {
+                0 => (CORRECT_COLOR, "errors"),
+                1 => (MISTYPE_COLOR, "error"),
+                _ => (MISTYPE_COLOR, "errors"),
+            };
+            status_block = status_block.title(
+                Line::styled(format!(" {errors} {noun} "), Style::new().fg(color)).right_aligned(),
+            );
+        }
+        Paragraph::new(status)
+            .block(status_block)
             .left_aligned()
             .wrap(Wrap { trim: true })
             .render(areas[0], buf);
+
+        if self.show_help {
+            let lines = self.help_lines();
+            let width = lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 2;
+            let popup = centered_rect(area, width, lines.len() as u16 + 2);
+            Clear.render(popup, buf);
+            Paragraph::new(lines)
+                .block(title_block("Help (paused)"))
+                .render(popup, buf);
+        }
+    }
+}
+
+/// The change since the previous test, green for better and red for worse
+fn comparison_line(comparison: Comparison) -> Line<'static> {
+    let delta = |change: f64, text: String| {
+        let color = if change > 0.0 {
+            CORRECT_COLOR
+        } else if change < 0.0 {
+            MISTYPE_COLOR
+        } else {
+            UNTYPED_COLOR
+        };
+        Span::styled(text, Style::new().fg(color))
+    };
+    Line::from(vec![
+        Span::raw("Since last test: "),
+        delta(comparison.wpm, format!("wpm {:+.0}", comparison.wpm)),
+        Span::raw(", "),
+        delta(
+            comparison.accuracy,
+            format!("accuracy {:+.1}%", comparison.accuracy),
+        ),
+    ])
+}
+
+/// A rectangle of the given size in the middle of the area, shrunk to fit if the area is smaller
+fn centered_rect(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+/// The (row, column) each word starts at when wrapped into lines of the given width
+/// Every word is followed by its separator space, and a word moves to the next line when it
+/// and its space do not fit. A word too long for any line gets a line to itself
+fn wrap_words(words: &[CursorWord], width: usize) -> Vec<(usize, usize)> {
+    let mut positions = Vec::with_capacity(words.len());
+    let (mut row, mut col) = (0, 0);
+    for cursor_word in words {
+        let word_width = cursor_word.word.chars.len() + 1;
+        if col > 0 && col + word_width > width {
+            row += 1;
+            col = 0;
+        }
+        positions.push((row, col));
+        col += word_width;
     }
+    positions
+}
+
+/// The number of lines to scroll so the active row sits at the anchor
+/// An anchor of 0 keeps the active row at the top of the view, 0.5 at the center and 1 at the
+/// bottom. The view never scrolls above the first line or past the last one, so the first lines
+/// of the text stay where they are until the active row reaches the anchor, and the last lines
+/// are typed below the anchor instead of leaving blank space under the text
+fn scroll_offset(active_row: usize, total_rows: usize, height: usize, anchor: f64) -> usize {
+    let anchor_row = (height.saturating_sub(1) as f64 * anchor).round() as usize;
+    let max_offset = total_rows.saturating_sub(height);
+    active_row.saturating_sub(anchor_row).min(max_offset)
 }
 
 /// Create a bordered block with a title.
-fn title_block(title: &str) -> Block {
+fn title_block(title: &str) -> Block<'_> {
     Block::bordered()
         .gray()
         .title(title.bold().into_centered_line())
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use ratatui::crossterm::event::KeyEvent;
+
+    use super::*;
+
+    fn args<S: AsRef<str>>(args: &[S]) -> impl Iterator<Item = String> {
+        args.iter()
+            .map(|arg| arg.as_ref().to_string())
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    /// The config for the command line flags on top of the flags saved by the last launch
+    fn config_with_last<S: AsRef<str>>(cli: &[&str], last: &[S]) -> Config {
+        let last = Config::from_args(args(last), Settings::default())
+            .unwrap()
+            .settings;
+        Config::from_args(args(cli), last).unwrap()
+    }
+
+    fn config_from(flags: &[&str]) -> Config {
+        Config::from_args(args(flags), Settings::default()).unwrap()
+    }
+
+    /// A path in the temporary directory, named so parallel tests do not clash
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("toqst-test-{}-{name}", std::process::id()))
+    }
+
+    /// A test of the given words with the given flags, the history is never saved
+    fn app_with(words: &[&str], flags: &[&str]) -> App {
+        let mut config = config_from(flags);
+        config.save_history = false;
+        config.history_file = temp_path("no-history.csv");
+        let words: Vec<String> = words.iter().map(|word| word.to_string()).collect();
+        App::new(words.iter().collect(), Vec::new(), None, config)
+    }
+
+    /// Type every character of the string, spaces included
+    fn type_str(app: &mut App, typed: &str) {
+        for ch in typed.chars() {
+            app.handle_char_read(ch);
+        }
+    }
+
+    #[test]
+    fn settings_are_defaults_then_last_settings_then_flags() {
+        let defaults = config_with_last::<&str>(&[], &[]);
+        assert_eq!(defaults.time_limit, Some(COUNTDOWN));
+        assert_eq!(defaults.theme, Theme::default());
+
+        let last = ["--time", "30", "--mistype-color", "blue"];
+        let remembered = config_with_last(&[], &last);
+        assert_eq!(remembered.time_limit, Some(30));
+        assert_eq!(remembered.theme.mistype.fg, Some(Color::Blue));
+
+        let overridden = config_with_last(&["--time", "60"], &last);
+        assert_eq!(overridden.time_limit, Some(60));
+        assert_eq!(overridden.theme.mistype.fg, Some(Color::Blue));
+    }
+
+    #[test]
+    fn mode_flags_replace_the_saved_time_and_completion() {
+        let saved = config_with_last::<&str>(&["--time-mode"], &[])
+            .settings
+            .args();
+        let word_mode = config_with_last(&["--word-mode"], &saved);
+        assert_eq!(word_mode.time_limit, None);
+        assert_eq!(word_mode.completion, CompletionMode::Either);
+
+        let saved = config_with_last::<&str>(&["--word-mode"], &[])
+            .settings
+            .args();
+        let time_mode = config_with_last(&["--time-mode"], &saved);
+        assert_eq!(time_mode.time_limit, Some(COUNTDOWN));
+        assert_eq!(time_mode.completion, CompletionMode::TimeOnly);
+    }
+
+    #[test]
+    fn implied_time_limits_are_not_saved() {
+        for flags in [
+            &["--endurance"][..],
+            &["--char-limit", "5"],
+            &["--correct-target", "5"],
+        ] {
+            let config = config_with_last::<&str>(flags, &[]);
+            assert_eq!(config.time_limit, None);
+            assert!(config.settings.args().is_empty());
+            let next_launch = config_with_last(&[], &config.settings.args());
+            assert_eq!(next_launch.time_limit, Some(COUNTDOWN));
+        }
+    }
+
+    #[test]
+    fn kps_counts_every_key_press() {
+        for flags in [&[][..], &["--count-spaces"]] {
+            let mut app = app_with(&["ab", "cd"], flags);
+            type_str(&mut app, "ab cx");
+            assert_eq!(app.cursor.key_presses(), 5);
+        }
+    }
+
+    #[test]
+    fn retyped_characters_are_timed_once() {
+        let mut app = app_with(&["ab", "cd"], &[]);
+        type_str(&mut app, "ab");
+        app.cursor.handle_delete();
+        app.cursor.handle_delete();
+        type_str(&mut app, "ab");
+        assert_eq!(app.cursor.correct_typed_at.len(), 2);
+    }
+
+    /// The style the space after the word is drawn with
+    fn separator_span_style(app: &App, word_idx: usize) -> Style {
+        let separator = CursorWord::new(" ");
+        app.style_for_screen(2 * word_idx + 1, &separator, None)[0].style
+    }
+
+    #[test]
+    fn separator_color_follows_the_advance() {
+        let mut app = app_with(&["ab", "cd", "ef"], &["--color-spaces"]);
+        type_str(&mut app, "ab cdx ");
+        let theme = app.cursor.theme;
+        assert_eq!(separator_span_style(&app, 0).fg, theme.correct.fg);
+        assert_eq!(separator_span_style(&app, 1).fg, theme.mistype.fg);
+    }
+
+    #[test]
+    fn frozen_key_in_place_of_the_space_makes_it_a_mistake() {
+        let mut app = app_with(&["ab", "cd"], &["--color-spaces", "--freeze-on-error"]);
+        type_str(&mut app, "abx ");
+        assert_eq!(
+            app.cursor.words[0].separator.get_typed_state(),
+            TypedState::Mistype
+        );
+    }
+
+    #[test]
+    fn grace_hides_a_wrong_space() {
+        let mut app = app_with(&["ab", "cd"], &["--color-spaces", "--grace", "60000"]);
+        type_str(&mut app, "abx ");
+        assert_eq!(
+            separator_span_style(&app, 0).fg,
+            app.cursor.theme.untyped.fg
+        );
+    }
+
+    #[test]
+    fn grace_keeps_the_caret_on_a_frozen_space() {
+        let mut app = app_with(&["ab", "cd"], &["--freeze-on-error", "--grace", "60000"]);
+        type_str(&mut app, "abx");
+        let style = separator_span_style(&app, 0);
+        assert_eq!(style.fg, app.cursor.theme.untyped.fg);
+        assert!(style.add_modifier.contains(app.cursor.cursor_modifier()));
+    }
+
+    fn clock_running(app: &App) -> bool {
+        match &app.user_typing {
+            TypingEvent::Typed(clock) => clock.running_since.is_some(),
+            TypingEvent::Afk => false,
+        }
+    }
+
+    #[test]
+    fn losing_focus_pauses_the_clock() {
+        let mut app = app_with(&["ab", "cd"], &["--pause-on-blur"]);
+        type_str(&mut app, "a");
+        app.handle_event(Event::FocusLost);
+        assert!(!clock_running(&app));
+        assert_eq!(app.notice.as_deref(), Some(App::PAUSE_NOTICE));
+        app.handle_event(Event::FocusGained);
+        assert!(clock_running(&app));
+        assert_eq!(app.notice, None);
+    }
+
+    #[test]
+    fn focus_is_ignored_without_pause_on_blur() {
+        let mut app = app_with(&["ab", "cd"], &[]);
+        type_str(&mut app, "a");
+        let notice = app.notice.clone();
+        app.handle_event(Event::FocusLost);
+        assert!(clock_running(&app));
+        assert!(!app.focus_lost);
+        assert_eq!(app.notice, notice);
+    }
+
+    #[test]
+    fn losing_focus_keeps_other_notices() {
+        let mut app = app_with(&["ab", "cd"], &["--pause-on-blur"]);
+        app.notice = Some(String::from("Reloaded the words"));
+        app.handle_event(Event::FocusLost);
+        app.handle_event(Event::FocusGained);
+        assert_eq!(app.notice.as_deref(), Some("Reloaded the words"));
+    }
+
+    #[test]
+    fn level_up_makes_the_test_harder() {
+        let mut config = config_from(&["--time", "30"]);
+        assert_eq!(config.level_up(), Some(String::from("40s")));
+        assert_eq!(config.time_limit, Some(30 + LEVEL_UP_SECONDS));
+
+        let mut config = config_from(&["--word-mode", "--correct-target", "5"]);
+        config.level_up();
+        assert_eq!(config.correct_target, Some(5 + LEVEL_UP_WORDS));
+
+        let mut config = config_from(&["--endurance"]);
+        assert_eq!(
+            config.level_up(),
+            Some(format!(
+                "{} words",
+                ENDURANCE_WORDS + LEVEL_UP_ENDURANCE_WORDS
+            ))
+        );
+
+        let mut config = config_from(&["--text", "a b"]);
+        assert_eq!(config.level_up(), None);
+    }
+
+    #[test]
+    fn level_up_keeps_the_config_when_the_words_can_not_load() {
+        let mut app = app_with(
+            &["ab"],
+            &["--time", "30", "--words", "/nonexistent/words.txt"],
+        );
+        app.level_up();
+        assert_eq!(app.config.time_limit, Some(30));
+        assert!(app
+            .notice
+            .as_deref()
+            .is_some_and(|notice| notice.starts_with("Could not reload the words")));
+    }
+
+    #[test]
+    fn untimed_goals_keep_a_time_limit_in_any_order() {
+        for goal in [
+            &["--correct-target", "5"][..],
+            &["--char-limit", "50"],
+            &["--endurance"],
+        ] {
+            let before = config_from(&[&["--time", "30"], goal].concat());
+            let after = config_from(&[goal, &["--time", "30"]].concat());
+            assert_eq!(before.time_limit, Some(30));
+            assert_eq!(after.time_limit, Some(30));
+            assert_eq!(config_from(goal).time_limit, None);
+        }
+    }
+
+    #[test]
+    fn imperfect_words_do_not_count_toward_the_target() {
+        let mut app = app_with(&["ab", "cd", "ef"], &["--correct-target", "2"]);
+        type_str(&mut app, "ab ");
+        assert_eq!(app.cursor.correct_words, 1);
+        type_str(&mut app, "cx ");
+        assert_eq!(app.cursor.correct_words, 1);
+    }
+
+    #[test]
+    fn timing_rows_hold_the_time_between_keystrokes() {
+        let mut app = app_with(&["ab"], &[]);
+        let start = time::Instant::now();
+        app.cursor.keystroke_log = [('a', 'a', 0), ('b', 'x', 150), (' ', ' ', 400)]
+            .map(|(expected, typed, millis)| Keystroke {
+                expected,
+                typed,
+                at: start + time::Duration::from_millis(millis),
+            })
+            .to_vec();
+        let rows = app.cursor.timing_rows();
+        let intervals: Vec<_> = rows.iter().map(|row| row.interval).collect();
+        assert_eq!(
+            intervals,
+            vec![
+                None,
+                Some(time::Duration::from_millis(150)),
+                Some(time::Duration::from_millis(250))
+            ]
+        );
+        let correct: Vec<_> = rows.iter().map(|row| row.correct).collect();
+        assert_eq!(correct, vec![true, false, true]);
+    }
+
+    #[test]
+    fn time_mode_refills_the_words() {
+        let pool: Vec<String> = (0..20).map(|idx| format!("w{idx}")).collect();
+        let mut config = config_from(&["--time-mode"]);
+        config.word_count = REFILL_THRESHOLD + 2;
+        let (words, refill_pool) = draw_words(&pool, &config);
+        let mut app = App::new(words, refill_pool, None, config);
+        while app.cursor.words.len() == REFILL_THRESHOLD + 2 {
+            let word = app.cursor.untyped_tail()[0].clone();
+            type_str(&mut app, &format!("{word} "));
+        }
+        assert_eq!(app.cursor.word_idx, 2);
+        assert_eq!(app.cursor.words.len(), REFILL_THRESHOLD + 2 + pool.len());
+    }
+
+    #[test]
+    fn word_mode_does_not_refill() {
+        let pool: Vec<String> = (0..20).map(|idx| format!("w{idx}")).collect();
+        let (_, refill_pool) = draw_words(&pool, &config_from(&["--word-mode"]));
+        assert!(refill_pool.is_empty());
+    }
+
+    /// Background of every cell of the word at the index, as it is drawn on the typing screen
+    fn word_backgrounds(app: &App, word_idx: usize) -> Vec<Option<Color>> {
+        let word = &app.cursor.words[word_idx];
+        app.cursor
+            .style_word(2 * word_idx, word)
+            .iter()
+            .map(|span| span.style.bg)
+            .collect()
+    }
+
+    #[test]
+    fn word_heat_tints_only_imperfect_words() {
+        let mut app = app_with(
+            &["ab", "cd", "ef"],
+            &["--word-heat", "--colors", "truecolor"],
+        );
+        type_str(&mut app, "ax cd e");
+        assert!(word_backgrounds(&app, 0)
+            .iter()
+            .all(|bg| *bg == Some(IMPERFECT_WORD_BG)));
+        assert!(!word_backgrounds(&app, 1).contains(&Some(IMPERFECT_WORD_BG)));
+        // The word being typed is not judged yet, even with a mistake in it
+        type_str(&mut app, "x");
+        assert!(!word_backgrounds(&app, 2).contains(&Some(IMPERFECT_WORD_BG)));
+    }
+
+    #[test]
+    fn tab_cycles_the_time_presets() {
+        let mut app = app_with(&["ab"], &[]);
+        let mut limits = Vec::new();
+        for _ in 0..=TIME_PRESETS.len() {
+            app.cycle_time_preset();
+            limits.push(app.config.time_limit);
+        }
+        assert_eq!(
+            limits,
+            vec![Some(15), Some(30), Some(60), Some(120), None, Some(15)]
+        );
+    }
+
+    #[test]
+    fn time_presets_skip_word_mode_when_finishing_on_time() {
+        let mut app = app_with(&["ab"], &["--time-mode"]);
+        for _ in 0..TIME_PRESETS.len() {
+            app.cycle_time_preset();
+            assert!(app.config.time_limit.is_some());
+        }
+    }
+
+    #[test]
+    fn time_presets_are_locked_once_typing() {
+        let mut app = app_with(&["ab"], &["--time", "45"]);
+        type_str(&mut app, "a");
+        app.cycle_time_preset();
+        assert_eq!(app.config.time_limit, Some(45));
+    }
+
+    #[test]
+    fn the_test_fails_on_the_mistake_past_the_budget() {
+        let mut app = app_with(&["abcd", "efgh"], &["--max-mistakes", "2"]);
+        type_str(&mut app, "xx");
+        assert!(!app.should_finish());
+        // Fixing a mistake does not give it back
+        app.cursor.handle_delete();
+        type_str(&mut app, "b");
+        assert!(!app.should_finish());
+        type_str(&mut app, "x");
+        assert!(app.should_finish());
+        app.finish().unwrap();
+        assert!(app.failure.is_some());
+    }
+
+    /// The text of each line of the failure diff of the test typed with no mistakes allowed
+    fn failure_text(typed: &str) -> Vec<String> {
+        let mut app = app_with(&["ab", "cde"], &["--max-mistakes", "0"]);
+        type_str(&mut app, typed);
+        app.finish().unwrap();
+        let failure = app.failure.unwrap();
+        app.failure_diff(&failure)
+            .iter()
+            .map(|line| line.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn failure_diff_of_a_substitution() {
+        assert_eq!(
+            failure_text("ab cx"),
+            vec![
+                "Failed on word 2 at character 2",
+                "cde",
+                "Expected 'd', typed 'x'"
+            ]
+        );
+    }
+
+    #[test]
+    fn failure_diff_of_an_extra_character() {
+        assert_eq!(
+            failure_text("abz"),
+            vec![
+                "Failed on word 1 at character 3",
+                "abz",
+                "Expected a space, typed 'z'"
+            ]
+        );
+    }
+
+    /// Lines in the history file after finishing a test with the flags
+    fn history_lines_after(name: &str, flags: &[&str]) -> usize {
+        let history_file = temp_path(name);
+        let mut config = config_from(flags);
+        config.history_file = history_file.clone();
+        let words = [String::from("ab")];
+        let mut app = App::new(words.iter().collect(), Vec::new(), None, config);
+        type_str(&mut app, "ab ");
+        app.finish().unwrap();
+        let lines = fs::read_to_string(&history_file).map_or(0, |history| history.lines().count());
+        let _ = fs::remove_file(history_file);
+        lines
+    }
+
+    #[test]
+    fn no_save_writes_no_history() {
+        assert_eq!(history_lines_after("saved.csv", &[]), 1);
+        assert_eq!(history_lines_after("not-saved.csv", &["--no-save"]), 0);
+    }
+
+    #[test]
+    fn endurance_records_a_checkpoint_every_interval() {
+        let words = ["a"; 2 * CHECKPOINT_INTERVAL + 1];
+        let mut app = app_with(&words, &["--endurance"]);
+        type_str(&mut app, &"a ".repeat(CHECKPOINT_INTERVAL - 1));
+        assert!(app.checkpoints().is_empty());
+        type_str(&mut app, "a ");
+        assert_eq!(app.checkpoints().len(), 1);
+        assert_eq!(app.checkpoints()[0].0, CHECKPOINT_INTERVAL);
+        // Finishing the same word again does not record it twice
+        app.cursor.handle_delete();
+        type_str(&mut app, " ");
+        assert_eq!(app.checkpoints().len(), 1);
+        type_str(&mut app, &"a ".repeat(CHECKPOINT_INTERVAL));
+        let words_done: Vec<_> = app.checkpoints().iter().map(|&(words, _)| words).collect();
+        assert_eq!(
+            words_done,
+            vec![CHECKPOINT_INTERVAL, 2 * CHECKPOINT_INTERVAL]
+        );
+    }
+
+    #[test]
+    fn only_endurance_records_checkpoints() {
+        let words = ["a"; CHECKPOINT_INTERVAL + 1];
+        let mut app = app_with(&words, &[]);
+        type_str(&mut app, &"a ".repeat(CHECKPOINT_INTERVAL));
+        assert!(app.checkpoints().is_empty());
+    }
+
+    #[test]
+    fn a_double_space_advances_once() {
+        let mut app = app_with(&["ab", "cd", "ef"], &[]);
+        type_str(&mut app, "ab  ");
+        assert_eq!(app.cursor.word_idx, 1);
+        // A space before starting is ignored too
+        let mut app = app_with(&["ab", "cd"], &[]);
+        type_str(&mut app, " ");
+        assert_eq!(app.cursor.word_idx, 0);
+        assert!(!app.has_started());
+    }
+
+    #[test]
+    fn word_skip_lets_a_double_space_skip_a_word() {
+        let mut app = app_with(&["ab", "cd", "ef"], &["--allow-word-skip"]);
+        type_str(&mut app, "ab  ");
+        assert_eq!(app.cursor.word_idx, 2);
+    }
+
+    fn word_states(app: &App, word_idx: usize) -> Vec<TypedState> {
+        app.cursor.words[word_idx]
+            .word
+            .chars
+            .iter()
+            .map(StyledChar::get_typed_state)
+            .collect()
+    }
+
+    #[test]
+    fn reverse_types_each_word_from_its_end() {
+        let mut app = app_with(&["abc", "de"], &["--reverse"]);
+        type_str(&mut app, "c");
+        assert_eq!(
+            word_states(&app, 0),
+            vec![
+                TypedState::Untyped,
+                TypedState::Untyped,
+                TypedState::Correct
+            ]
+        );
+        // The caret moves toward the start of the word
+        assert_eq!(app.cursor.get_cursor_word().char_idx(true), 1);
+        type_str(&mut app, "xa");
+        assert_eq!(
+            word_states(&app, 0),
+            vec![
+                TypedState::Correct,
+                TypedState::Mistype,
+                TypedState::Correct
+            ]
+        );
+    }
+
+    #[test]
+    fn scroll_offset_is_clamped_at_the_edges() {
+        for anchor in [0.0, 0.5, 1.0] {
+            // The first line never scrolls up past the top
+            assert_eq!(scroll_offset(0, 20, 5, anchor), 0);
+            // The last line never leaves space below the text
+            assert_eq!(scroll_offset(19, 20, 5, anchor), 15);
+            // Text shorter than the view is never scrolled
+            assert_eq!(scroll_offset(3, 4, 5, anchor), 0);
+        }
+    }
+
+    #[test]
+    fn scroll_offset_keeps_the_active_line_at_the_anchor() {
+        assert_eq!(scroll_offset(10, 20, 5, 0.0), 10);
+        assert_eq!(scroll_offset(10, 20, 5, 0.5), 8);
+        assert_eq!(scroll_offset(10, 20, 5, 1.0), 6);
+    }
+
+    #[test]
+    fn counting_spaces_raises_the_wpm() {
+        let minute = time::Duration::from_secs(60);
+        let typed = "abcd efgh ijxl ";
+        let mut app = app_with(&["abcd", "efgh", "ijkl", "mnop"], &[]);
+        type_str(&mut app, typed);
+        assert_eq!(app.cursor.correct_chars(), 11);
+        assert_eq!(wpm(app.cursor.correct_chars(), minute), 2.2);
+        // Only the spaces after the two perfect words count
+        let mut app = app_with(&["abcd", "efgh", "ijkl", "mnop"], &["--count-spaces"]);
+        type_str(&mut app, typed);
+        assert_eq!(app.cursor.correct_chars(), 13);
+        assert_eq!(wpm(app.cursor.correct_chars(), minute), 2.6);
+    }
+
+    #[test]
+    fn a_snapshot_is_written_after_the_interval() {
+        let mut app = app_with(&["ab", "cd"], &[]);
+        let interval = time::Duration::from_millis(20);
+        app.config.autosave_interval = Some(interval);
+        app.config.autosave_file = temp_path("autosave.json");
+        // The space counts as a keystroke even without --count-spaces
+        type_str(&mut app, "ab c");
+        app.autosave();
+        assert!(app.autosave_write.is_none());
+        thread::sleep(interval);
+        app.autosave();
+        app.autosave_write.take().unwrap().join().unwrap().unwrap();
+        let snapshot = fs::read_to_string(&app.config.autosave_file).unwrap();
+        assert!(snapshot.contains("\"chars_typed\":4"));
+        // The snapshot is removed once the test is saved properly
+        app.finish().unwrap();
+        assert!(!app.config.autosave_file.exists());
+    }
+
+    /// Foreground of every cell of the word at the index, as it is drawn on the typing screen
+    fn screen_colors(app: &App, word_idx: usize) -> Vec<Option<Color>> {
+        let word = &app.cursor.words[word_idx];
+        app.style_for_screen(2 * word_idx, word, None)
+            .iter()
+            .map(|span| span.style.fg)
+            .collect()
+    }
+
+    #[test]
+    fn typing_on_does_not_reveal_a_mistake_in_its_grace() {
+        let mut app = app_with(&["abcd", "ef"], &["--grace", "60000"]);
+        type_str(&mut app, "axc");
+        let theme = app.cursor.theme;
+        assert_eq!(screen_colors(&app, 0)[1], theme.untyped.fg);
+        assert_eq!(screen_colors(&app, 0)[2], theme.correct.fg);
+
+        let mut app = app_with(&["abcd", "ef"], &["--grace", "0"]);
+        type_str(&mut app, "axc");
+        assert_eq!(screen_colors(&app, 0)[1], theme.mistype.fg);
+    }
+
+    #[test]
+    fn reloading_picks_up_a_changed_word_file() {
+        let word_file = temp_path("reload.txt");
+        fs::write(&word_file, "alpha\n").unwrap();
+        let path = word_file.to_str().unwrap();
+        let mut app = app_with(&["alpha"], &["--words", path]);
+        fs::write(&word_file, "beta\n").unwrap();
+        app.reload_words();
+        assert_eq!(app.cursor.untyped_tail(), vec!["beta"]);
+        // A file that can no longer be read keeps the words of the current test
+        fs::remove_file(&word_file).unwrap();
+        app.reload_words();
+        assert_eq!(app.cursor.untyped_tail(), vec!["beta"]);
+        assert!(app
+            .notice
+            .as_deref()
+            .is_some_and(|notice| notice.starts_with("Could not reload the words")));
+    }
+
+    /// Whether the test is over in each completion mode, once the words or the time are done
+    fn finishes(mode: &str, words_done: bool, time_done: bool) -> bool {
+        let mut app = app_with(&["ab", "cd"], &["--finish", mode, "--time", "60"]);
+        type_str(&mut app, if words_done { "ab cd " } else { "a" });
+        if time_done {
+            app.config.time_limit = Some(0);
+        }
+        app.should_finish()
+    }
+
+    #[test]
+    fn each_completion_mode_finishes_on_its_condition() {
+        assert!(finishes("words", true, false));
+        assert!(!finishes("words", false, true));
+        assert!(!finishes("time", true, false));
+        assert!(finishes("time", false, true));
+        assert!(finishes("either", true, false));
+        assert!(finishes("either", false, true));
+        for mode in ["words", "time", "either"] {
+            assert!(!finishes(mode, false, false));
+        }
+    }
+
+    #[test]
+    fn extra_characters_look_different_from_mistypes() {
+        let mut app = app_with(&["ab", "cd"], &[]);
+        type_str(&mut app, "axz ");
+        let word = &app.cursor.words[0];
+        let styles: Vec<Style> = app
+            .cursor
+            .style_word(0, word)
+            .iter()
+            .map(|span| span.style)
+            .collect();
+        assert_eq!(styles[1], app.cursor.theme.mistype);
+        assert_eq!(styles[2], app.cursor.theme.mistype_extra);
+        assert_ne!(styles[1], styles[2]);
+    }
+
+    #[test]
+    fn the_caret_cell_has_the_caret_colors() {
+        let flags = [
+            "--caret-fg",
+            "yellow",
+            "--caret-bg",
+            "blue",
+            "--colors",
+            "16",
+        ];
+        let mut app = app_with(&["abc", "de"], &flags);
+        type_str(&mut app, "a");
+        let word = &app.cursor.words[0];
+        let caret = app.cursor.style_word(0, word)[1].style;
+        assert_eq!(caret.fg, Some(Color::Yellow));
+        assert_eq!(caret.bg, Some(Color::Blue));
+        assert!(caret
+            .add_modifier
+            .contains(Modifier::BOLD | Modifier::UNDERLINED));
+        // A mistake under the caret keeps its own color
+        let mut mistake = StyledChar::new('b');
+        mistake.switch_typed_state(TypedState::Mistype);
+        let theme = app.cursor.theme;
+        let style = mistake.caret_style(&theme, app.cursor.caret_style());
+        assert_eq!(style.fg, theme.mistype.fg);
+        assert_eq!(style.bg, Some(Color::Blue));
+    }
+
+    #[test]
+    fn text_is_typed_as_its_tokens_in_order() {
+        let config = config_from(&["--text", " the quick\tbrown  fox "]);
+        let pool = load_pool(&config).unwrap();
+        let (words, refill_pool) = draw_words(&pool, &config);
+        let app = App::new(words, refill_pool, None, config);
+        assert_eq!(
+            app.cursor.untyped_tail(),
+            vec!["the", "quick", "brown", "fox"]
+        );
+        assert!(app.cursor.refill_pool.is_empty());
+    }
+
+    #[test]
+    fn empty_text_is_an_error() {
+        assert!(load_pool(&config_from(&["--text", " \t "])).is_err());
+    }
+
+    /// A test drawn from a pool of three words when ten are asked for
+    fn small_pool_app(flags: &[&str]) -> App {
+        let pool = ["one", "two", "three"].map(String::from);
+        let mut config = config_from(flags);
+        config.word_count = 10;
+        let (words, refill_pool) = draw_words(&pool, &config);
+        App::new(words, refill_pool, None, config)
+    }
+
+    #[test]
+    fn a_small_pool_gives_a_short_test_with_a_warning() {
+        let app = small_pool_app(&[]);
+        assert_eq!(app.cursor.words.len(), 3);
+        assert_eq!(
+            app.notice.as_deref(),
+            Some("Only 3 of 10 words are in the pool, use --repeat-words to repeat them")
+        );
+    }
+
+    #[test]
+    fn repeat_words_fills_the_word_count() {
+        let app = small_pool_app(&["--repeat-words"]);
+        assert_eq!(app.cursor.words.len(), 10);
+        assert!(app
+            .cursor
+            .untyped_tail()
+            .iter()
+            .all(|word| ["one", "two", "three"].contains(&word.as_str())));
+        assert_eq!(app.notice, None);
+    }
+
+    #[test]
+    fn freeze_on_error_counts_every_wrong_attempt() {
+        let mut app = app_with(&["abc", "de"], &["--freeze-on-error"]);
+        type_str(&mut app, "axy");
+        assert_eq!(app.cursor.get_cursor_word().cursor_idx, 1);
+        assert_eq!(word_states(&app, 0)[1], TypedState::Mistype);
+        type_str(&mut app, "b");
+        assert_eq!(app.cursor.get_cursor_word().cursor_idx, 2);
+        assert_eq!(word_states(&app, 0)[1], TypedState::Correct);
+        assert_eq!(app.cursor.wrong_attempts, 2);
+        assert_eq!(app.cursor.mistakes_made, 2);
+        // Two characters took four keys
+        assert_eq!(app.attempts_per_char(), 2.0);
+    }
+
+    #[test]
+    fn relaxed_spacing_starts_the_next_word_without_a_space() {
+        let mut app = app_with(&["ab", "cd"], &["--relaxed-spacing"]);
+        type_str(&mut app, "abc");
+        assert_eq!(app.cursor.word_idx, 1);
+        assert_eq!(app.cursor.get_cursor_word().cursor_idx, 1);
+        assert_eq!(
+            app.cursor.words[0].separator.get_typed_state(),
+            TypedState::Mistype
+        );
+    }
+
+    #[test]
+    fn without_relaxed_spacing_the_key_is_an_extra_character() {
+        let mut app = app_with(&["ab", "cd"], &[]);
+        type_str(&mut app, "abc");
+        assert_eq!(app.cursor.word_idx, 0);
+        assert_eq!(word_states(&app, 0)[2], TypedState::MistypeExtra);
+    }
+
+    #[test]
+    fn only_the_words_ahead_are_visible() {
+        let words = ["a", "b", "c", "d", "e", "f"];
+        let visible = |app: &App| -> Vec<usize> {
+            (0..words.len())
+                .filter(|&idx| app.is_word_visible(idx))
+                .collect()
+        };
+        let mut app = app_with(&words, &["--visible-words", "2"]);
+        assert_eq!(visible(&app), vec![0, 1, 2]);
+        type_str(&mut app, "a b ");
+        assert_eq!(visible(&app), vec![2, 3, 4]);
+        // Near the end only the words left are shown
+        type_str(&mut app, "c d e ");
+        assert_eq!(visible(&app), vec![5]);
+        let app = app_with(&words, &[]);
+        assert_eq!(visible(&app), vec![0, 1, 2, 3, 4, 5]);
+    }
+
+    /// Ten characters typed over a paused minute, so the live wpm is exactly 2
+    fn app_at_two_wpm(flags: &[&str]) -> App {
+        let mut app = app_with(&["abcde", "fghij", "k"], flags);
+        type_str(&mut app, "abcde fghij ");
+        app.user_typing = TypingEvent::Typed(TypingClock {
+            banked: time::Duration::from_secs(60),
+            running_since: None,
+        });
+        app
+    }
+
+    #[test]
+    fn pace_delta_is_positive_when_ahead() {
+        assert_eq!(
+            app_at_two_wpm(&["--target-wpm", "1.5"]).pace_delta(),
+            Some(0.5)
+        );
+        assert_eq!(
+            app_at_two_wpm(&["--target-wpm", "3"]).pace_delta(),
+            Some(-1.0)
+        );
+        assert_eq!(app_at_two_wpm(&[]).pace_delta(), None);
+    }
+
+    fn press(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
+        app.handle_event(Event::Key(KeyEvent::new(code, modifiers)));
+    }
+
+    #[test]
+    fn shortcuts_are_not_typed() {
+        let mut app = app_with(&["ab", "cd"], &[]);
+        press(&mut app, KeyCode::Char('a'), KeyModifiers::CONTROL);
+        press(&mut app, KeyCode::Char('a'), KeyModifiers::ALT);
+        assert_eq!(app.cursor.get_cursor_word().cursor_idx, 0);
+        assert!(!app.has_started());
+        press(&mut app, KeyCode::Char('A'), KeyModifiers::SHIFT);
+        assert_eq!(app.cursor.get_cursor_word().cursor_idx, 1);
+        // AltGr shows up as Ctrl+Alt and types the character
+        press(
+            &mut app,
+            KeyCode::Char('b'),
+            KeyModifiers::CONTROL | KeyModifiers::ALT,
+        );
+        assert_eq!(app.cursor.get_cursor_word().cursor_idx, 2);
+    }
+
+    /// Where the caret is after typing part of the second word and pressing the key
+    fn caret_after(code: KeyCode, flags: &[&str]) -> (usize, usize) {
+        let mut app = app_with(&["ab", "cde"], flags);
+        type_str(&mut app, "ab cd");
+        press(&mut app, code, KeyModifiers::NONE);
+        (app.cursor.word_idx, app.cursor.get_cursor_word().cursor_idx)
+    }
+
+    #[test]
+    fn delete_follows_its_configured_policy() {
+        assert_eq!(caret_after(KeyCode::Backspace, &[]), (1, 1));
+        assert_eq!(caret_after(KeyCode::Delete, &[]), (1, 1));
+        assert_eq!(
+            caret_after(KeyCode::Delete, &["--delete-key", "word"]),
+            (1, 0)
+        );
+        assert_eq!(
+            caret_after(KeyCode::Delete, &["--delete-key", "none"]),
+            (1, 2)
+        );
+        // Backspace always erases a single character
+        let flags = ["--delete-key", "word"];
+        assert_eq!(caret_after(KeyCode::Backspace, &flags), (1, 1));
+    }
+
+    #[test]
+    fn the_error_count_goes_down_when_mistakes_are_fixed() {
+        let mut app = app_with(&["abc", "de", "fg"], &[]);
+        type_str(&mut app, "axcz ");
+        assert_eq!(app.cursor.visible_error_count(), 2);
+        type_str(&mut app, "x");
+        assert_eq!(app.cursor.visible_error_count(), 3);
+        app.cursor.handle_delete();
+        assert_eq!(app.cursor.visible_error_count(), 2);
+        // Going back into the last word and erasing the extra character fixes it too
+        app.cursor.handle_delete();
+        app.cursor.handle_delete();
+        assert_eq!(app.cursor.visible_error_count(), 1);
+    }
+
+    #[test]
+    fn a_drill_is_typed_as_its_tokens() {
+        let config = config_from(&["--drill", "alphabet"]);
+        let pool = load_pool(&config).unwrap();
+        assert_eq!(pool, vec!["abcdefghijklmnopqrstuvwxyz"]);
+        let config = config_from(&["--drill", "home-row"]);
+        let pool = load_pool(&config).unwrap();
+        assert_eq!(pool[..4], ["asdf", "jkl;", "asdf", "jkl;"]);
+        assert_eq!(pool.last().map(String::as_str), Some("fall"));
+    }
+
+    #[test]
+    fn an_unknown_drill_is_an_error() {
+        let err = Config::from_args(args(&["--drill", "nope"]), Settings::default()).unwrap_err();
+        assert!(err.to_string().starts_with("Unknown drill: nope"));
+        assert_eq!(drill_text("nope"), None);
+    }
+
+    #[test]
+    fn char_limit_ends_on_exactly_the_last_correct_character() {
+        let mut app = app_with(&["ab", "cde", "fg"], &["--char-limit", "4"]);
+        type_str(&mut app, "ab cx");
+        assert!(!app.should_finish());
+        app.cursor.handle_delete();
+        // The limit is reached in the middle of the word that straddles it
+        type_str(&mut app, "d");
+        assert_eq!(app.cursor.correct_letters(), 4);
+        assert!(app.should_finish());
+    }
+
+    #[test]
+    fn results_ignore_a_new_test_until_the_delay() {
+        let word_file = temp_path("results-delay.txt");
+        fs::write(&word_file, "ab\n").unwrap();
+        let flags = [
+            "--words",
+            word_file.to_str().unwrap(),
+            "--results-delay",
+            "50",
+        ];
+        let mut app = app_with(&["ab"], &flags);
+        type_str(&mut app, "ab ");
+        app.finish().unwrap();
+        press(&mut app, KeyCode::Char('l'), KeyModifiers::NONE);
+        assert!(app.screen == Screen::Results);
+        thread::sleep(time::Duration::from_millis(50));
+        press(&mut app, KeyCode::Char('l'), KeyModifiers::NONE);
+        assert!(app.screen == Screen::Typing);
+        fs::remove_file(word_file).unwrap();
+    }
+
+    #[test]
+    fn results_can_always_be_left() {
+        let mut app = app_with(&["ab"], &[]);
+        type_str(&mut app, "ab ");
+        app.finish().unwrap();
+        press(&mut app, KeyCode::Esc, KeyModifiers::NONE);
+        assert!(app.should_exit);
+    }
+
+    #[test]
+    fn continuing_types_the_words_left() {
+        let mut app = app_with(
+            &["ab", "cd", "ef"],
+            &["--time", "60", "--results-delay", "0"],
+        );
+        type_str(&mut app, "ab c");
+        app.config.time_limit = Some(0);
+        app.finish().unwrap();
+        assert!(app.can_continue());
+        let tail = app.cursor.untyped_tail();
+        assert_eq!(tail, vec!["cd", "ef"]);
+        press(&mut app, KeyCode::Char('c'), KeyModifiers::NONE);
+        assert!(app.screen == Screen::Typing);
+        assert_eq!(app.cursor.untyped_tail(), tail);
+        assert_eq!(app.segments.len(), 1);
+    }
+
+    #[test]
+    fn word_heat_stays_visible_with_16_colors() {
+        let mut app = app_with(&["ab", "cd"], &["--word-heat", "--colors", "16"]);
+        type_str(&mut app, "ax ");
+        let tint = app.cursor.imperfect_word_bg();
+        assert!(!matches!(tint, Color::Black | Color::Reset));
+        assert!(word_backgrounds(&app, 0).iter().all(|bg| *bg == Some(tint)));
+    }
+
+    #[test]
+    fn stats_report_summarizes_the_history_file() {
+        let history_file = temp_path("stats-history.csv");
+        let at = |day, hour| {
+            Local
+                .with_ymd_and_hms(2024, 3, day, hour, 0, 0)
+                .unwrap()
+                .to_rfc3339()
+        };
+        let history = [
+            format!("{},50.00,95.00,30.00", at(9, 12)),
+            // Written before the duration was recorded
+            format!("{},70.00,98.00", at(10, 9)),
+            String::new(),
+            format!("{},60.00,97.00,15.00", at(10, 18)),
+        ];
+        fs::write(&history_file, history.join("\n")).unwrap();
+        let today = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        assert_eq!(
+            stats_report(&history_file, false, today).unwrap(),
+            "Tests taken: 3\nAverage WPM: 60.0\nBest WPM:    70.0\nStreak:      2 day(s)\n"
+        );
+        assert_eq!(
+            stats_report(&history_file, true, today).unwrap(),
+            "{\"tests\":3,\"average_wpm\":60.00,\"best_wpm\":70.00,\"streak\":2}\n"
+        );
+        fs::remove_file(history_file).unwrap();
+    }
+
+    #[test]
+    fn stats_report_of_a_missing_history_is_empty() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        let report = stats_report(&temp_path("missing-history.csv"), true, today).unwrap();
+        assert_eq!(
+            report,
+            "{\"tests\":0,\"average_wpm\":0.00,\"best_wpm\":0.00,\"streak\":0}\n"
+        );
+    }
+}